@@ -0,0 +1,51 @@
+use std::{fmt, io, path::{Path, PathBuf}};
+
+use serde::de::DeserializeOwned;
+
+/// Blad wczytywania albo sprawdzania danych wejsciowych.
+#[derive(Debug)]
+pub enum Blad {
+    Plik(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+    /// Oferty rozszerzaja sie nawzajem w petli, sciezka to plik wczytany po raz drugi.
+    CyklRozszerzen(PathBuf),
+    Dane(String),
+}
+
+impl fmt::Display for Blad {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Blad::Plik(path, blad) => write!(f, "nie mozna odczytac `{}`: {}", path.display(), blad),
+            Blad::Json(path, blad) => write!(f, "niepoprawny plik `{}`: {}", path.display(), blad),
+            Blad::CyklRozszerzen(path) => write!(f, "oferta `{}` rozszerza sama siebie", path.display()),
+            Blad::Dane(opis) => write!(f, "{}", opis),
+        }
+    }
+}
+
+impl std::error::Error for Blad {}
+
+/// Wczytuje plik JSON do podanego typu.
+pub fn wczytaj_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, Blad> {
+    let path = path.as_ref();
+    let data = std::fs::read_to_string(path).map_err(|blad| Blad::Plik(path.to_path_buf(), blad))?;
+
+    serde_json::from_str(&data).map_err(|blad| Blad::Json(path.to_path_buf(), blad))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Gospodarstwo, Koszty, Kredyt};
+
+    #[test]
+    fn brak_pliku_jest_bledem() {
+        assert!(matches!(Koszty::wczytaj("data/koszty/nie_ma.json"), Err(Blad::Plik(..))));
+        assert!(matches!(Kredyt::wczytaj("data/kredyt/nie_ma.json"), Err(Blad::Plik(..))));
+    }
+
+    #[test]
+    fn plik_innego_typu_jest_bledem() {
+        assert!(matches!(Gospodarstwo::wczytaj("data/koszty/pawia.json"), Err(Blad::Json(..))));
+    }
+}
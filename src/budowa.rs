@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, Koszt, KosztKoncowy, Kwota, Okres, Operator};
+
+/// Etap budowy zakonczony wyplata transzy w miesiacu `miesiac`, w procentach kwoty kredytu.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Etap {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    #[serde(rename = "miesiac")]
+    pub miesiac: u64,
+
+    #[serde(rename = "procent")]
+    pub procent: f64,
+}
+
+/// Kredyt na budowe domu: kwota jest wyplacana w transzach po kolejnych etapach, a w trakcie
+/// budowy splacane sa tylko odsetki od wyplaconej czesci.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Budowa {
+    #[serde(rename = "etapy")]
+    pub etapy: Vec<Etap>,
+
+    /// Pierwsza rata kapitalowo-odsetkowa, domyslnie miesiac po ostatniej transzy.
+    #[serde(rename = "karencja", default)]
+    pub karencja: Option<u64>,
+
+    /// Oplata za inspekcje banku przed wyplata kazdej transzy poza pierwsza.
+    #[serde(rename = "inspekcja", default)]
+    pub inspekcja: Kwota,
+}
+
+impl Budowa {
+    /// Etapy musza miec dodatnie transze i rozne nazwy (nazwa wyroznia koszt inspekcji),
+    /// a razem wyplacic cala kwote kredytu.
+    pub fn sprawdz(&self) -> Result<(), Blad> {
+        for (i, etap) in self.etapy.iter().enumerate() {
+            if etap.procent <= 0.0 {
+                return Err(Blad::Dane(format!("transza etapu `{}` musi byc dodatnia, podano {}%", etap.nazwa, etap.procent)));
+            }
+
+            if self.etapy[..i].iter().any(|e| e.nazwa == etap.nazwa) {
+                return Err(Blad::Dane(format!("etap `{}` wystepuje wiecej niz raz", etap.nazwa)));
+            }
+        }
+
+        let procent: f64 = self.etapy.iter().map(|e| e.procent).sum();
+
+        if (procent - 100.0).abs() > 1e-9 {
+            return Err(Blad::Dane(format!("transze sumuja sie do {}% kwoty kredytu", procent)));
+        }
+
+        Ok(())
+    }
+
+    /// Kwoty transz wg miesiaca wyplaty. Ostatnia transza rozlicza kredyt - wyrownuje zaokraglenia
+    /// tak, aby transze sumowaly sie do kwoty kredytu.
+    pub fn transze(&self, kwota_kredytowania: Kwota) -> BTreeMap<u64, Kwota> {
+        let mut transze = BTreeMap::new();
+        let mut wyplacono = Kwota::ZERO;
+
+        for (i, etap) in self.etapy.iter().enumerate() {
+            let transza = if i + 1 == self.etapy.len() {
+                kwota_kredytowania - wyplacono
+            } else {
+                kwota_kredytowania * (etap.procent / 100.0)
+            };
+
+            *transze.entry(etap.miesiac).or_insert(Kwota::ZERO) += transza;
+            wyplacono += transza;
+        }
+
+        transze
+    }
+
+    pub fn karencja(&self) -> u64 {
+        self.karencja.unwrap_or_else(|| self.etapy.iter().map(|e| e.miesiac + 1).max().unwrap_or(0))
+    }
+
+    /// Oplaty za inspekcje, kazda naliczana w miesiacu wyplaty transzy.
+    pub fn koszty(&self, liczba_rat: u64) -> BTreeMap<String, KosztKoncowy> {
+        self.etapy.iter()
+            .skip(1)
+            .filter(|_| self.inspekcja > Kwota::ZERO)
+            .map(|etap| {
+                let nazwa = format!("inspekcja ({})", etap.nazwa);
+                let koszt = Koszt::builder()
+                    .nazwa(nazwa.clone())
+                    .wartosc(self.inspekcja.zl())
+                    .operator(Operator::Stala)
+                    .okres(Okres::Jednorazowy)
+                    .build()
+                    .oblicz(Kwota::ZERO, liczba_rat)
+                    .od_raty(etap.miesiac);
+
+                (nazwa, koszt)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kalkulator, Kredyt};
+
+    fn etap(miesiac: u64, procent: f64) -> Etap {
+        Etap { nazwa: format!("etap {}", miesiac), miesiac, procent }
+    }
+
+    #[test]
+    fn ostatnia_transza_wyrownuje_do_kwoty_kredytu() {
+        let budowa = Budowa { etapy: vec![etap(0, 33.3), etap(6, 33.3), etap(12, 33.4)], karencja: None, inspekcja: Kwota::ZERO };
+        let transze = budowa.transze(Kwota::from(100_000.01));
+
+        assert_eq!(transze[&0], Kwota::from(33_300.0));
+        assert_eq!(transze.values().copied().sum::<Kwota>(), Kwota::from(100_000.01));
+        assert_eq!(budowa.karencja(), 13);
+    }
+
+    #[test]
+    fn transze_nie_sumujace_sie_do_100_procent_sa_bledem() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/mbank_budowa.json").unwrap();
+        kredyt.budowa.as_mut().unwrap().etapy.pop();
+
+        assert!(Kalkulator::new(kredyt).is_err());
+    }
+
+    #[test]
+    fn ujemna_transza_jest_bledem() {
+        let budowa = Budowa { etapy: vec![etap(0, 120.0), etap(6, -20.0)], karencja: None, inspekcja: Kwota::ZERO };
+
+        assert!(budowa.sprawdz().is_err());
+    }
+
+    #[test]
+    fn powtorzona_nazwa_etapu_jest_bledem() {
+        let mut budowa = Budowa { etapy: vec![etap(0, 50.0), etap(6, 50.0)], karencja: None, inspekcja: Kwota::from(300.0) };
+        assert!(budowa.sprawdz().is_ok());
+
+        budowa.etapy[1].nazwa = budowa.etapy[0].nazwa.clone();
+        assert!(budowa.sprawdz().is_err());
+    }
+}
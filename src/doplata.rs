@@ -0,0 +1,160 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Kwota, Rata, Zaokraglenie};
+
+/// Zdarzenie, po ktorym kredytobiorca traci doplaty.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum WarunekUtraty {
+    /// Suma nadplat w pierwszych `do_raty` ratach przekracza `maksymalnie`.
+    Nadplata { do_raty: u64, maksymalnie: Kwota },
+    /// Wynajem nieruchomosci od raty `od_raty`.
+    Wynajem { od_raty: u64 },
+}
+
+/// Program doplat do rat, np. Bezpieczny Kredyt 2%: przez `okres` rat panstwo doplaca roznice
+/// miedzy rata rynkowa a rata liczona przy oprocentowaniu `oprocentowanie`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Doplata {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    #[serde(rename = "oprocentowanie")]
+    pub oprocentowanie: f64,
+
+    #[serde(rename = "okres")]
+    pub okres: u64,
+
+    /// Najwyzsza kwota kredytu uprawniajaca do doplat.
+    #[serde(rename = "maksymalna_kwota", default)]
+    pub maksymalna_kwota: Option<Kwota>,
+
+    #[serde(rename = "warunki_utraty", default)]
+    pub warunki_utraty: Vec<WarunekUtraty>,
+
+    /// Czy po utracie doplat trzeba zwrocic doplaty juz otrzymane.
+    #[serde(rename = "zwrot", default)]
+    pub zwrot: bool,
+}
+
+/// Utrata doplat w racie `od_raty`.
+#[derive(Clone)]
+pub struct UtrataDoplaty {
+    pub od_raty: u64,
+    pub powod: String,
+}
+
+impl fmt::Display for UtrataDoplaty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "utrata doplat od {}. raty: {}", self.od_raty + 1, self.powod)
+    }
+}
+
+impl Doplata {
+    /// Pierwsze zdarzenie w okresie programu, po ktorym doplaty sa tracone. Zdarzenia po koncu
+    /// programu nie maja znaczenia - doplaty zostaly juz wyplacone.
+    fn utrata(&self, kwota_kredytowania: Kwota, mapa_rat: &BTreeMap<u64, Rata>) -> Option<UtrataDoplaty> {
+        if let Some(maksymalna_kwota) = self.maksymalna_kwota.filter(|&m| kwota_kredytowania > m) {
+            return Some(UtrataDoplaty { od_raty: 0, powod: format!("kwota kredytu powyzej {:.2}zl", maksymalna_kwota) });
+        }
+
+        self.warunki_utraty.iter()
+            .filter_map(|warunek| match *warunek {
+                WarunekUtraty::Nadplata { do_raty, maksymalnie } => {
+                    let mut nadplacono = Kwota::ZERO;
+
+                    mapa_rat.range(..do_raty)
+                        .find(|(_, rata)| {
+                            nadplacono += rata.nadplata;
+                            nadplacono > maksymalnie
+                        })
+                        .map(|(&numer_raty, _)| UtrataDoplaty {
+                            od_raty: numer_raty + 1,
+                            powod: format!("nadplaty przed {}. rata powyzej {:.2}zl", do_raty + 1, maksymalnie),
+                        })
+                },
+                WarunekUtraty::Wynajem { od_raty } => Some(UtrataDoplaty { od_raty, powod: "wynajem nieruchomosci".to_string() }),
+            })
+            .filter(|utrata| utrata.od_raty < self.okres)
+            .min_by_key(|utrata| utrata.od_raty)
+    }
+
+    /// Wpisuje doplaty do harmonogramu. Rata kredytobiorcy jest liczona od biezacego salda
+    /// i pozostalego okresu kredytowania przy oprocentowaniu programu, zaokraglana jak raty oferty.
+    pub fn zastosuj(&self, kwota_kredytowania: Kwota, okres_kredytowania: u64, zaokraglenie: Zaokraglenie, mapa_rat: &mut BTreeMap<u64, Rata>) -> Option<UtrataDoplaty> {
+        let utrata = self.utrata(kwota_kredytowania, mapa_rat);
+        let koniec = utrata.as_ref().map_or(self.okres, |u| u.od_raty.min(self.okres));
+        let mut kapital_do_splaty = kwota_kredytowania;
+        let mut otrzymano = Kwota::ZERO;
+
+        for (&numer_raty, rata) in mapa_rat.iter_mut() {
+            if numer_raty < koniec {
+                let pozostalo_rat = okres_kredytowania.saturating_sub(numer_raty).max(1);
+                let rata_programu = Rata::new(kapital_do_splaty, self.oprocentowanie, pozostalo_rat, zaokraglenie).wartosc();
+
+                rata.doplata = (rata.wartosc() - rata_programu).max(Kwota::ZERO);
+                otrzymano += rata.doplata;
+            } else if numer_raty == koniec && self.zwrot && utrata.is_some() {
+                rata.doplata = -otrzymano;
+            }
+
+            kapital_do_splaty -= rata.kapital();
+        }
+
+        utrata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KalkulatorRaty, Nadplaty};
+
+    fn doplata(warunki_utraty: Vec<WarunekUtraty>) -> Doplata {
+        Doplata {
+            nazwa: "Bezpieczny Kredyt 2%".to_string(),
+            oprocentowanie: 2.0,
+            okres: 12,
+            maksymalna_kwota: None,
+            warunki_utraty,
+            zwrot: true,
+        }
+    }
+
+    fn harmonogram(doplata: &Doplata) -> (BTreeMap<u64, Rata>, Option<UtrataDoplaty>) {
+        let kwota = Kwota::from(100_000.0);
+        let mut mapa_rat = KalkulatorRaty::new(kwota, 7.0, 120, Nadplaty::default()).mapa_rat();
+        let utrata = doplata.zastosuj(kwota, 120, Zaokraglenie::WGore, &mut mapa_rat);
+
+        (mapa_rat, utrata)
+    }
+
+    #[test]
+    fn zdarzenie_po_koncu_programu_nie_odbiera_doplat() {
+        let (mapa_rat, utrata) = harmonogram(&doplata(vec![WarunekUtraty::Wynajem { od_raty: 12 }]));
+
+        assert!(utrata.is_none());
+        assert!(mapa_rat.values().all(|r| r.doplata >= Kwota::ZERO));
+        assert_eq!(mapa_rat[&12].doplata, Kwota::ZERO);
+    }
+
+    #[test]
+    fn wynajem_w_okresie_programu_wymaga_zwrotu_doplat() {
+        let (mapa_rat, utrata) = harmonogram(&doplata(vec![WarunekUtraty::Wynajem { od_raty: 6 }]));
+        let otrzymano: Kwota = mapa_rat.range(..6).map(|(_, r)| r.doplata).sum();
+
+        assert_eq!(utrata.map(|u| u.od_raty), Some(6));
+        assert_eq!(mapa_rat[&6].doplata, -otrzymano);
+    }
+
+    #[test]
+    fn rata_programu_zaokraglana_jak_raty_oferty() {
+        let (mapa_rat, _) = harmonogram(&doplata(Vec::new()));
+        let rata = KalkulatorRaty::new(Kwota::from(100_000.0), 7.0, 120, Nadplaty::default()).mapa_rat()[&0];
+        let rata_programu = Rata::new(Kwota::from(100_000.0), 2.0, 120, Zaokraglenie::WGore).wartosc();
+
+        assert_eq!(mapa_rat[&0].doplata, rata.wartosc() - rata_programu);
+    }
+}
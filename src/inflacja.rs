@@ -0,0 +1,107 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, Kwota};
+
+/// Porownanie ofert w dzisiejszych pieniadzach. Koszt realny dyskontuje odsetki i koszty inflacja,
+/// NPV dyskontuje wszystkie platnosci (z kapitalem) stopa dyskontowa i odejmuje wyplacony kredyt.
+#[derive(Serialize, Deserialize)]
+pub struct PorownanieRealne {
+    #[serde(rename = "oferty")]
+    pub oferty: Vec<String>,
+
+    /// Roczna inflacja w procentach.
+    #[serde(rename = "inflacja")]
+    pub inflacja: f64,
+
+    /// Roczna stopa dyskontowa w procentach, domyslnie rowna inflacji.
+    #[serde(rename = "stopa_dyskontowa", default)]
+    pub stopa_dyskontowa: Option<f64>,
+}
+
+pub struct WierszRealny {
+    pub nazwa: String,
+    pub liczba_rat: u64,
+    pub koszt_nominalny: Kwota,
+    pub koszt_realny: Kwota,
+    pub npv: Kwota,
+}
+
+pub struct WynikPorownaniaRealnego {
+    pub inflacja: f64,
+    pub stopa_dyskontowa: f64,
+    pub wiersze: Vec<WierszRealny>,
+}
+
+impl PorownanieRealne {
+    pub fn oblicz(&self) -> Result<WynikPorownaniaRealnego, Blad> {
+        let stopa_dyskontowa = self.stopa_dyskontowa.unwrap_or(self.inflacja);
+
+        let wiersze = self.oferty.iter().map(|oferta| {
+            let kalkulator = Kalkulator::new(Kredyt::wczytaj(oferta)?)?;
+
+            Ok(WierszRealny {
+                nazwa: kalkulator.nazwa().to_string(),
+                liczba_rat: kalkulator.mapa_rat().len() as u64,
+                koszt_nominalny: kalkulator.koszt_kredytu(None),
+                koszt_realny: kalkulator.koszt_realny(self.inflacja),
+                npv: kalkulator.npv(stopa_dyskontowa),
+            })
+        })
+        .collect::<Result<_, Blad>>()?;
+
+        Ok(WynikPorownaniaRealnego { inflacja: self.inflacja, stopa_dyskontowa, wiersze })
+    }
+}
+
+impl fmt::Display for WynikPorownaniaRealnego {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Porownanie ofert (inflacja {:.2}%, stopa dyskontowa {:.2}%):", self.inflacja, self.stopa_dyskontowa)?;
+
+        for w in self.wiersze.iter() {
+            writeln!(
+                f,
+                "    * `{}` ({} rat): koszt nominalny {:.2}zl, koszt realny {:.2}zl, NPV {:.2}zl",
+                w.nazwa, w.liczba_rat, w.koszt_nominalny, w.koszt_realny, w.npv
+            )?;
+        }
+
+        if let Some(najlepsza) = self.wiersze.iter().min_by_key(|w| w.npv) {
+            writeln!(f, "Najnizszy koszt w dzisiejszych pieniadzach: `{}`", najlepsza.nazwa)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn porownanie(inflacja: f64) -> WierszRealny {
+        PorownanieRealne {
+            oferty: vec!["data/kredyt/pekao_sa_25.json".to_string()],
+            inflacja,
+            stopa_dyskontowa: None,
+        }.oblicz().unwrap().wiersze.remove(0)
+    }
+
+    #[test]
+    fn bez_inflacji_koszt_realny_i_npv_rowne_nominalnemu() {
+        let wiersz = porownanie(0.0);
+
+        assert!((wiersz.koszt_realny - wiersz.koszt_nominalny).grosze().abs() <= 1);
+        assert!((wiersz.npv - wiersz.koszt_nominalny).grosze().abs() <= 1);
+    }
+
+    #[test]
+    fn inflacja_obniza_koszt_realny() {
+        let nominalny = porownanie(0.0);
+        let wiersz = porownanie(5.0);
+
+        assert_eq!(wiersz.koszt_nominalny, nominalny.koszt_nominalny);
+        assert!(wiersz.koszt_realny < nominalny.koszt_realny);
+        assert!(wiersz.npv < wiersz.koszt_realny);
+    }
+}
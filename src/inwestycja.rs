@@ -0,0 +1,185 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, KalkulatorRaty, Kwota, Nadplata, Nadplaty, Rata};
+
+/// Podatek od zyskow kapitalowych (podatek Belki) w procentach.
+pub const PODATEK_BELKI: f64 = 19.0;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum Inwestycja {
+    /// Lokata kapitalizowana co rok, podatek pobierany przy kazdej kapitalizacji.
+    Lokata { oprocentowanie: f64 },
+    /// Dziesiecioletnie obligacje EDO: w pierwszym roku stale `oprocentowanie`, w kolejnych
+    /// `inflacja` plus `marza`. Podatek pobierany przy wykupie, po ktorym srodki sa reinwestowane.
+    Edo { oprocentowanie: f64, marza: f64, inflacja: f64 },
+}
+
+impl Inwestycja {
+    const OKRES_EDO: u64 = 120;
+
+    /// Wartosc wplaty `kwota` po `miesiecy` miesiacach, po podatku.
+    pub fn wartosc(&self, kwota: Kwota, miesiecy: u64) -> Kwota {
+        let netto = 1.0 - PODATEK_BELKI / 100.0;
+
+        match *self {
+            Inwestycja::Lokata { oprocentowanie } => {
+                let stopa = oprocentowanie / 100.0 * netto;
+
+                kwota * ((1.0 + stopa).powi((miesiecy / 12) as i32) * (1.0 + stopa * (miesiecy % 12) as f64 / 12.0))
+            },
+            Inwestycja::Edo { oprocentowanie, marza, inflacja } => {
+                let mut wartosc = kwota;
+                let mut pozostalo = miesiecy;
+
+                while pozostalo > 0 {
+                    let okres = pozostalo.min(Self::OKRES_EDO);
+                    let wspolczynnik: f64 = (0..okres.div_ceil(12))
+                        .map(|rok| {
+                            let stopa = if rok == 0 { oprocentowanie } else { inflacja + marza } / 100.0;
+                            let miesiecy_w_roku = (okres - rok * 12).min(12);
+
+                            1.0 + stopa * miesiecy_w_roku as f64 / 12.0
+                        })
+                        .product();
+
+                    wartosc = wartosc + (wartosc * (wspolczynnik - 1.0)) * netto;
+                    pozostalo -= okres;
+                }
+
+                wartosc
+            },
+        }
+    }
+}
+
+/// Porownanie nadplat oferty z inwestowaniem tych samych kwot do konca okresu kredytowania.
+/// Nadplata obniza kolejne raty - aby porownanie bylo uczciwe, roznice w ratach sa inwestowane
+/// na tych samych warunkach co wplaty w sciezce inwestycyjnej.
+#[derive(Serialize, Deserialize)]
+pub struct NadplataCzyInwestycja {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "inwestycja")]
+    pub inwestycja: Inwestycja,
+}
+
+pub struct WierszInwestycji {
+    pub nadplata: Nadplata,
+    pub wplacono: Kwota,
+    /// Odsetki, ktore trzeba by zaplacic bez tej nadplaty (przy pozostalych nadplatach bez zmian).
+    pub oszczednosc_na_odsetkach: Kwota,
+    /// Wartosc zainwestowanych obnizek rat na koniec okresu kredytowania.
+    pub wartosc_nadplaty: Kwota,
+    /// Wartosc zainwestowanych wplat na koniec okresu kredytowania, po podatku.
+    pub wartosc_inwestycji: Kwota,
+}
+
+impl WierszInwestycji {
+    pub fn oplaca_sie_nadplata(&self) -> bool {
+        self.wartosc_nadplaty >= self.wartosc_inwestycji
+    }
+}
+
+pub struct WynikInwestycji {
+    pub nazwa: String,
+    pub wiersze: Vec<WierszInwestycji>,
+}
+
+impl NadplataCzyInwestycja {
+    fn mapa_rat(kredyt: &Kredyt, nadplaty: Nadplaty) -> BTreeMap<u64, Rata> {
+        KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), kredyt)
+            .nadplaty(nadplaty)
+            .mapa_rat()
+    }
+
+    /// Wartosc wplat `(miesiac, kwota)` zainwestowanych do konca okresu kredytowania.
+    fn wartosc(&self, wplaty: impl Iterator<Item = (u64, Kwota)>, okres_kredytowania: u64) -> Kwota {
+        wplaty.map(|(miesiac, kwota)| self.inwestycja.wartosc(kwota, okres_kredytowania.saturating_sub(miesiac))).sum()
+    }
+
+    pub fn oblicz(&self) -> Result<WynikInwestycji, Blad> {
+        let kredyt = Kredyt::wczytaj(&self.oferta)?;
+        let wszystkie_nadplaty = kredyt.wszystkie_nadplaty();
+        let nadplaty: Vec<Nadplata> = wszystkie_nadplaty.iter().cloned().collect();
+        let z_nadplatami = Self::mapa_rat(&kredyt, wszystkie_nadplaty);
+
+        let wiersze = nadplaty.iter().enumerate().map(|(i, nadplata)| {
+            let mut pozostale = nadplaty.clone();
+            pozostale.remove(i);
+            let bez_nadplaty = Self::mapa_rat(&kredyt, Nadplaty::new(pozostale));
+
+            // faktycznie nadplacone kwoty - po splacie kredytu nie ma czego nadplacac ani inwestowac
+            let wplaty: Vec<(u64, Kwota)> = bez_nadplaty.keys()
+                .map(|&miesiac| (miesiac, Nadplaty::new(vec![nadplata.clone()]).wartosc(miesiac)))
+                .filter(|&(_, kwota)| kwota > Kwota::ZERO)
+                .collect();
+            let obnizki_rat = bez_nadplaty.iter().map(|(&miesiac, rata)| {
+                let rata_z_nadplata = z_nadplatami.get(&miesiac).map(|r| r.wartosc()).unwrap_or_default();
+
+                (miesiac, rata.wartosc() - rata_z_nadplata)
+            });
+            let odsetki = |mapa_rat: &BTreeMap<u64, Rata>| mapa_rat.values().map(|r| r.odsetki).sum::<Kwota>();
+
+            WierszInwestycji {
+                nadplata: nadplata.clone(),
+                wplacono: wplaty.iter().map(|&(_, kwota)| kwota).sum(),
+                oszczednosc_na_odsetkach: odsetki(&bez_nadplaty) - odsetki(&z_nadplatami),
+                wartosc_nadplaty: self.wartosc(obnizki_rat, kredyt.okres_kredytowania),
+                wartosc_inwestycji: self.wartosc(wplaty.into_iter(), kredyt.okres_kredytowania),
+            }
+        })
+        .collect();
+
+        Ok(WynikInwestycji { nazwa: kredyt.nazwa, wiersze })
+    }
+}
+
+impl fmt::Display for WynikInwestycji {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Nadplata czy inwestycja (`{}`), wartosci na koniec okresu kredytowania:", self.nazwa)?;
+
+        for w in self.wiersze.iter() {
+            writeln!(
+                f,
+                "    * Nadplata {} - {} ({:.2}zl): oszczednosc na odsetkach {:.2}zl, nadplata warta {:.2}zl, inwestycja {:.2}zl - lepiej: {}",
+                w.nadplata.from, w.nadplata.to, w.wplacono, w.oszczednosc_na_odsetkach, w.wartosc_nadplaty, w.wartosc_inwestycji,
+                if w.oplaca_sie_nadplata() { "nadplata" } else { "inwestycja" }
+            )?;
+        }
+
+        let wartosc_nadplat: Kwota = self.wiersze.iter().map(|w| w.wartosc_nadplaty).sum();
+        let wartosc_inwestycji: Kwota = self.wiersze.iter().map(|w| w.wartosc_inwestycji).sum();
+
+        writeln!(f, "Razem: nadplaty warte {:.2}zl, inwestycje {:.2}zl", wartosc_nadplat, wartosc_inwestycji)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porownuje_tez_wlasne_nadplaty_kredytobiorcow() {
+        let wynik = NadplataCzyInwestycja {
+            oferta: "data/kredyt/pekao_sa_25_wspolny.json".to_string(),
+            inwestycja: Inwestycja::Lokata { oprocentowanie: 5.0 },
+        }.oblicz().unwrap();
+        let wlasna = wynik.wiersze.iter().find(|w| w.nadplata.from == 24).unwrap();
+
+        assert_eq!(wynik.wiersze.len(), 2);
+        assert_eq!(wlasna.wplacono, Kwota::from(20_000.0));
+        assert!(wlasna.oszczednosc_na_odsetkach > Kwota::ZERO);
+    }
+
+    #[test]
+    fn lokata_kapitalizowana_co_rok_po_podatku() {
+        let lokata = Inwestycja::Lokata { oprocentowanie: 10.0 };
+
+        assert_eq!(lokata.wartosc(Kwota::from(1_000.0), 24), Kwota::from(1_000.0 * 1.081 * 1.081));
+        assert_eq!(lokata.wartosc(Kwota::from(1_000.0), 6), Kwota::from(1_040.5));
+    }
+}
@@ -1,50 +1,94 @@
-use std::{collections::BTreeMap, fmt, cmp};
+use std::{collections::BTreeMap, fmt};
 
-use crate::{KosztKoncowy, kredyt::Kredyt, Koszt, Operator, Okres, Nadplaty, mapa_rat::{Rata, KalkulatorRaty, MapaRat}};
+use crate::{Blad, KosztKoncowy, KosztWarunku, kredyt::Kredyt, Koszt, Kredytobiorca, UdzialKredytobiorcy, UtrataDoplaty, Kwota, Operator, Okres, StrukturaSplaty, KursyWaluty, Nadplaty, WartoscNieruchomosci, mapa_rat::{Rata, MapaRat}};
 
 // TODO % posiadanej hipoteki po x latach
 // TODO nadplaty
 
 pub struct Kalkulator {
     nazwa: String,
-    wartosc_hipoteki: f64,
+    wartosc_hipoteki: Kwota,
     wklad_wlasny: f64,
     oprocentowanie: f64,
-    kwota_kredytowania: f64,
+    kwota_kredytowania: Kwota,
     okres_kredytowania: u64,
-    calkowity_koszt_nieruchomosci: f64,
-    koszt_kredytu: f64,
+    calkowity_koszt_nieruchomosci: Kwota,
+    koszt_kredytu: Kwota,
     mapa_kosztow: BTreeMap<String, KosztKoncowy>,
     mapa_rat: BTreeMap<u64, Rata>,
     nadplaty: Nadplaty,
+    wartosc_nieruchomosci: WartoscNieruchomosci,
+    kursy: Option<KursyWaluty>,
+    doplata: Option<(String, Option<UtrataDoplaty>)>,
+    kredytobiorcy: Vec<Kredytobiorca>,
+    transze: BTreeMap<u64, Kwota>,
+    struktura_splaty: StrukturaSplaty,
+    kredyt: Kredyt,
 }
 
 impl Kalkulator {
-    pub fn new(dto: Kredyt) -> Self {
-        let kwota_kredytowania = dto.wartosc_hipoteki - dto.wartosc_hipoteki * dto.wklad_wlasny / 100.0;
-        let mapa_kosztow = Self::mapa_kosztow(&dto);
+    pub fn new(dto: Kredyt) -> Result<Self, Blad> {
+        let kwota_kredytowania = dto.kwota_kredytowania();
+        dto.sprawdz()?;
+        let kredyt = dto.clone();
+        let kursy = dto.waluta.as_ref().map(KursyWaluty::new).transpose()?;
+        let kwota_w_walucie = kursy.as_ref().map_or(kwota_kredytowania, |k| k.w_walucie(kwota_kredytowania));
+        let mut mapa_rat = MapaRat::new(kwota_w_walucie, &dto).mapa_rat().clone();
+        let mut nowa_mapa_rat = BTreeMap::new();
+        let mut koszty_refinansowania = BTreeMap::new();
 
-        let mapa_rat = MapaRat::new(kwota_kredytowania, &dto).mapa_rat().clone();
+        if let Some(refinansowanie) = &dto.refinansowanie {
+            mapa_rat.split_off(&refinansowanie.od_raty);
+            let kapital_do_splaty = kwota_w_walucie - mapa_rat.values().map(|r| r.kapital()).sum::<Kwota>();
+
+            if kapital_do_splaty > Kwota::ZERO {
+                let kapital_w_zlotych = kursy.as_ref().map_or(kapital_do_splaty, |k| kapital_do_splaty * k.sprzedaz(refinansowanie.od_raty));
+
+                nowa_mapa_rat = refinansowanie.mapa_rat(kapital_do_splaty, &dto);
+                koszty_refinansowania = refinansowanie.mapa_kosztow(kapital_w_zlotych, nowa_mapa_rat.len() as u64, &dto);
+            }
+        }
+
+        let od_refinansowania = nowa_mapa_rat.keys().next().copied().unwrap_or(u64::MAX);
+        mapa_rat.extend(nowa_mapa_rat);
+
+        if let Some(kursy) = &kursy {
+            mapa_rat = kursy.w_zlotych(kwota_kredytowania, &mapa_rat);
+        }
+
+        let doplata = dto.doplata.as_ref()
+            .map(|d| (d.nazwa.clone(), d.zastosuj(kwota_kredytowania, dto.okres_kredytowania, dto.zaokraglenie.metoda, &mut mapa_rat)));
+
+        // koszty miesieczne sa naliczane tylko do splaty kredytu, takze wczesniejszej
+        let mut mapa_kosztow = Self::oblicz_koszty(&dto, &mapa_rat.range(..od_refinansowania).map(|(n, r)| (*n, *r)).collect(), kursy.as_ref())?;
+        mapa_kosztow.extend(koszty_refinansowania);
 
         let koszt_kredytu = Self::koszt_kredytu_internal(&mapa_rat, &mapa_kosztow, dto.okres_kredytowania, None);
         let calkowity_koszt_nieruchomosci = dto.wartosc_hipoteki + koszt_kredytu;
 
-        Self {
+        Ok(Self {
             nazwa: dto.nazwa,
             wartosc_hipoteki: dto.wartosc_hipoteki,
             wklad_wlasny: dto.wklad_wlasny,
             oprocentowanie: dto.oprocentowanie,
             kwota_kredytowania,
             okres_kredytowania: dto.okres_kredytowania,
+            wartosc_nieruchomosci: WartoscNieruchomosci::new(dto.wartosc_hipoteki, &dto.zmiana_wartosci),
             nadplaty: dto.nadplaty,
             calkowity_koszt_nieruchomosci,
             koszt_kredytu,
             mapa_kosztow,
             mapa_rat,
-        }
+            kursy,
+            doplata,
+            kredytobiorcy: dto.kredytobiorcy,
+            transze: dto.budowa.as_ref().map(|b| b.transze(kwota_kredytowania)).unwrap_or_default(),
+            struktura_splaty: dto.struktura_splaty,
+            kredyt,
+        })
     }
 
-    pub fn koszt_kredytu(&self, numer_raty: impl Into<Option<u64>>) -> f64 {
+    pub fn koszt_kredytu(&self, numer_raty: impl Into<Option<u64>>) -> Kwota {
         Self::koszt_kredytu_internal(&self.mapa_rat, &self.mapa_kosztow, self.okres_kredytowania, numer_raty)
     }
 
@@ -53,38 +97,284 @@ impl Kalkulator {
         mapa_kosztow: &BTreeMap<String, KosztKoncowy>,
         okres_kredytowania: u64,
         numer_raty: impl Into<Option<u64>>
-    ) -> f64 {
-        let numer_raty = numer_raty.into().unwrap_or(okres_kredytowania);
+    ) -> Kwota {
+        let ostatnia_rata = mapa_rat.keys().next_back().map_or(0, |n| n + 1);
+        let numer_raty = numer_raty.into().unwrap_or_else(|| okres_kredytowania.max(ostatnia_rata));
+
+        mapa_rat.range(0..numer_raty).map(|(_, v)| v.koszt()).sum::<Kwota>()
+        + mapa_kosztow.values().map(|k| k.do_raty(numer_raty)).sum::<Kwota>()
+    }
+
+    /// Koszt niedotrzymania kazdego warunku marzy i rezygnacji z kazdego produktu oferty.
+    pub fn koszty_warunkow(&self) -> Result<Vec<KosztWarunku>, Blad> {
+        KosztWarunku::oblicz(&self.kredyt)
+    }
+
+    pub fn nazwa(&self) -> &str {
+        &self.nazwa
+    }
+
+    pub fn wartosc_hipoteki(&self) -> Kwota {
+        self.wartosc_hipoteki
+    }
+
+    pub fn wklad_wlasny(&self) -> f64 {
+        self.wklad_wlasny
+    }
+
+    pub fn oprocentowanie(&self) -> f64 {
+        self.oprocentowanie
+    }
+
+    pub fn kwota_kredytowania(&self) -> Kwota {
+        self.kwota_kredytowania
+    }
+
+    pub fn okres_kredytowania(&self) -> u64 {
+        self.okres_kredytowania
+    }
+
+    /// Wartosc nieruchomosci powiekszona o calkowity koszt kredytu.
+    pub fn calkowity_koszt_nieruchomosci(&self) -> Kwota {
+        self.calkowity_koszt_nieruchomosci
+    }
+
+    /// Koszt kredytu za caly okres, rowny `koszt_kredytu(None)`.
+    pub fn calkowity_koszt_kredytu(&self) -> Kwota {
+        self.koszt_kredytu
+    }
+
+    pub fn mapa_kosztow(&self) -> &BTreeMap<String, KosztKoncowy> {
+        &self.mapa_kosztow
+    }
+
+    pub fn mapa_rat(&self) -> &BTreeMap<u64, Rata> {
+        &self.mapa_rat
+    }
+
+    /// Nadplaty wspolne, bez wlasnych nadplat kredytobiorcow.
+    pub fn nadplaty(&self) -> &Nadplaty {
+        &self.nadplaty
+    }
+
+    pub fn kredytobiorcy(&self) -> &[Kredytobiorca] {
+        &self.kredytobiorcy
+    }
+
+    pub fn kursy(&self) -> Option<&KursyWaluty> {
+        self.kursy.as_ref()
+    }
+
+    /// Kapital pozostaly do splaty po `numer_raty` ratach. Dla kredytu walutowego - w zlotych
+    /// po kursie sprzedazy z miesiaca `numer_raty`.
+    pub fn kapital_do_splaty(&self, numer_raty: u64) -> Kwota {
+        match &self.kursy {
+            Some(kursy) => self.kapital_do_splaty_w_walucie(numer_raty) * kursy.sprzedaz(numer_raty),
+            None => self.kapital_ksiegowy(numer_raty),
+        }
+    }
+
+    /// Kapital pozostaly do splaty w walucie kredytu.
+    pub fn kapital_do_splaty_w_walucie(&self, numer_raty: u64) -> Kwota {
+        match &self.kursy {
+            Some(kursy) => self.kapital_ksiegowy(numer_raty) * (1.0 / kursy.kupno(0)),
+            None => self.kapital_ksiegowy(numer_raty),
+        }
+    }
+
+    /// Roznice kursowe zaplacone w pierwszych `numer_raty` ratach.
+    pub fn roznice_kursowe(&self, numer_raty: u64) -> Kwota {
+        self.mapa_rat.range(0..numer_raty).map(|(_, r)| r.roznice_kursowe + r.roznice_kursowe_nadplaty).sum()
+    }
+
+    /// Doplaty do rat otrzymane w pierwszych `numer_raty` ratach, pomniejszone o zwrocone.
+    pub fn doplaty(&self, numer_raty: u64) -> Kwota {
+        self.mapa_rat.range(0..numer_raty).map(|(_, r)| r.doplata).sum()
+    }
+
+    pub fn utrata_doplaty(&self) -> Option<&UtrataDoplaty> {
+        self.doplata.as_ref().and_then(|(_, utrata)| utrata.as_ref())
+    }
+
+    /// Kapital wyplacony, a jeszcze niesplacony, po kursie z dnia uruchomienia kredytu.
+    fn kapital_ksiegowy(&self, numer_raty: u64) -> Kwota {
+        self.kwota_kredytowania
+            - self.mapa_rat.range(0..numer_raty).map(|(_, r)| r.kapital()).sum::<Kwota>()
+            - self.transze.range(numer_raty..).map(|(_, t)| *t).sum::<Kwota>()
+    }
+
+    /// Wartosc nieruchomosci po `numer_raty` miesiacach, z uwzglednieniem `zmiana_wartosci` oferty.
+    pub fn wartosc_nieruchomosci(&self, numer_raty: u64) -> Kwota {
+        self.wartosc_nieruchomosci.w_miesiacu(numer_raty)
+    }
+
+    /// Czesc nieruchomosci nieobciazona kredytem.
+    pub fn kapital_wlasny(&self, numer_raty: u64) -> Kwota {
+        self.wartosc_nieruchomosci(numer_raty) - self.kapital_do_splaty(numer_raty)
+    }
+
+    /// Biezace LTV w procentach, `None` gdy nieruchomosc nie ma wartosci.
+    pub fn ltv(&self, numer_raty: u64) -> Option<f64> {
+        let wartosc = self.wartosc_nieruchomosci(numer_raty);
+
+        if wartosc > Kwota::ZERO {
+            Some(self.kapital_do_splaty(numer_raty).zl() * 100.0 / wartosc.zl())
+        } else {
+            None
+        }
+    }
+
+    /// Kapital wlasny pomniejszony o wszystko, co zostalo wplacone do `numer_raty`:
+    /// wklad wlasny, splacony kapital, odsetki i koszty.
+    pub fn bilans(&self, numer_raty: u64) -> Kwota {
+        let wplacono = self.wartosc_hipoteki - self.kapital_ksiegowy(numer_raty) + self.koszt_kredytu(numer_raty);
+
+        self.kapital_wlasny(numer_raty) - wplacono
+    }
+
+    /// Platnosci w kolejnych miesiacach: pod kluczem 0 koszty poniesione przy uruchomieniu kredytu,
+    /// pod kluczem `n` rata `n - 1` (z nadplata) i koszty naliczone w `n`-tym miesiacu.
+    pub fn przeplywy(&self) -> BTreeMap<u64, Kwota> {
+        self.przeplywy_internal(Rata::wplata)
+    }
+
+    /// Koszt kredytu (odsetki i koszty) w cenach z dnia uruchomienia kredytu, przy rocznej `inflacja` w procentach.
+    pub fn koszt_realny(&self, inflacja: f64) -> Kwota {
+        Kwota::from(Self::wartosc_biezaca(&self.przeplywy_internal(|r| r.koszt()), inflacja))
+    }
 
-        mapa_rat.range(0..numer_raty).map(|(_, v)| v.odsetki).sum::<f64>()
-        + mapa_kosztow.iter().map(|(_, k)| k.oblicz(0.0, numer_raty).wartosc()).sum::<f64>()
+    /// Wartosc biezaca wszystkich platnosci pomniejszona o wyplacony kredyt,
+    /// czyli koszt kredytu w dzisiejszych pieniadzach przy rocznej `stopa_dyskontowa` w procentach.
+    pub fn npv(&self, stopa_dyskontowa: f64) -> Kwota {
+        Kwota::from(Self::wartosc_biezaca(&self.przeplywy(), stopa_dyskontowa)) - self.kwota_kredytowania
+    }
+
+    /// Rzeczywista roczna stopa oprocentowania w procentach: stopa, przy ktorej wartosc biezaca
+    /// wszystkich platnosci (rat, nadplat i kosztow pomniejszonych o premie) jest rowna wartosci
+    /// biezacej wyplat kredytu. `None`, gdy takiej stopy nie ma w przedziale -99% - 1000%.
+    pub fn rrso(&self) -> Option<f64> {
+        let przeplywy = self.przeplywy();
+        let wyplaty = match self.transze.is_empty() {
+            true => std::iter::once((0, self.kwota_kredytowania)).collect(),
+            false => self.transze.clone(),
+        };
+        let saldo = |stopa: f64| Self::wartosc_biezaca(&przeplywy, stopa) - Self::wartosc_biezaca(&wyplaty, stopa);
+        let (mut dolna, mut gorna) = (-99.0, 1000.0);
+
+        if saldo(dolna) < 0.0 || saldo(gorna) > 0.0 {
+            return None;
+        }
+
+        for _ in 0..200 {
+            let srodek = (dolna + gorna) / 2.0;
+
+            if saldo(srodek) > 0.0 {
+                dolna = srodek;
+            } else {
+                gorna = srodek;
+            }
+        }
+
+        Some((dolna + gorna) / 2.0)
+    }
+
+    fn przeplywy_internal(&self, z_raty: impl Fn(&Rata) -> Kwota) -> BTreeMap<u64, Kwota> {
+        let koniec = self.okres_kredytowania.max(self.mapa_rat.keys().next_back().map_or(0, |n| n + 1));
+        let koszty_do = |numer_raty: u64| self.mapa_kosztow.values().map(|k| k.do_raty(numer_raty)).sum::<Kwota>();
+
+        (0..=koniec)
+            .map(|n| {
+                let koszty = match n {
+                    0 => koszty_do(0),
+                    n => koszty_do(n) - koszty_do(n - 1),
+                };
+                let rata = n.checked_sub(1).and_then(|m| self.mapa_rat.get(&m)).map(&z_raty).unwrap_or_default();
+
+                (n, koszty + rata)
+            })
+            .collect()
+    }
+
+    /// Wartosc biezaca w zlotych - przy skrajnych stopach przekracza zakres `Kwota`.
+    fn wartosc_biezaca(przeplywy: &BTreeMap<u64, Kwota>, stopa_roczna: f64) -> f64 {
+        let dyskonto = 1.0 + stopa_roczna / 100.0;
+
+        przeplywy.iter().map(|(&n, &kwota)| kwota.zl() * dyskonto.powf(-(n as f64) / 12.0)).sum()
     }
 
     pub fn procent_hipoteki(&self, numer_raty: impl Into<Option<u64>>) -> f64 {
         let numer_raty = numer_raty.into().unwrap_or(self.okres_kredytowania);
-        let wplacony_kapital: f64 = self.mapa_rat.range(0..numer_raty).map(|(_, v)| v.kapital()).sum();
-        let splacony_procent = wplacony_kapital * 100.0 / self.kwota_kredytowania;
+        let wplacony_kapital: Kwota = self.mapa_rat.range(0..numer_raty).map(|(_, v)| v.kapital()).sum();
+        let splacony_procent = wplacony_kapital.zl() * 100.0 / self.kwota_kredytowania.zl();
 
         // self.wklad_wlasny + splacony_procent
         splacony_procent
     }
 
-    fn mapa_kosztow(dto: &Kredyt) -> BTreeMap<String, KosztKoncowy> {
-        let mut koszty: BTreeMap<String, KosztKoncowy> = dto.koszty.iter().map(|k| (k.nazwa(), k.oblicz(dto.wartosc_hipoteki - dto.wartosc_hipoteki * dto.wklad_wlasny / 100.0, dto.okres_kredytowania))).collect();
-        let pcc = dto.wartosc_hipoteki * 2.0 / 100.0;
-        let pcc = KosztKoncowy::new(
-                Koszt::builder()
-                    .nazwa("PCC")
-                    .wartosc(2.0)
-                    .operator(Operator::Procent)
-                    .okres(Okres::Jednorazowy)
-                    .build(),
-                pcc
-            );
+    fn oblicz_koszty(dto: &Kredyt, mapa_rat: &BTreeMap<u64, Rata>, kursy: Option<&KursyWaluty>) -> Result<BTreeMap<String, KosztKoncowy>, Blad> {
+        let liczba_rat = mapa_rat.len() as u64;
+        let mut koszty: BTreeMap<String, KosztKoncowy> = dto.koszty.iter()
+            .map(|k| {
+                let okres = k.do_ltv().map_or(liczba_rat, |ltv| Self::raty_do_ltv(dto, mapa_rat, kursy, ltv));
+
+                (k.nazwa(), k.oblicz(dto.kwota_kredytowania(), okres))
+            })
+            .collect();
+        let pcc = Koszt::builder()
+            .nazwa("PCC")
+            .wartosc(2.0)
+            .operator(Operator::Procent)
+            .okres(Okres::Jednorazowy)
+            .build()
+            .oblicz(dto.wartosc_hipoteki, liczba_rat);
+
+        for produkt in dto.produkty.iter() {
+            if let Some(koszt) = produkt.koszt(dto.wartosc_hipoteki, liczba_rat) {
+                koszty.insert(produkt.nazwa.clone(), koszt);
+            }
+        }
+
+        for premia in dto.premie.iter() {
+            koszty.insert(premia.koszt.nazwa(), premia.oblicz(dto, liczba_rat)?);
+        }
+
+        // przy budowie domu nie ma zakupu z rynku wtornego, wiec nie ma tez PCC
+        match &dto.budowa {
+            Some(budowa) => koszty.extend(budowa.koszty(liczba_rat)),
+            None => { koszty.insert("PCC".to_string(), pcc); },
+        }
+
+        if let Some(prowizja) = &dto.prowizja_za_nadplate {
+            let nadplaty: Kwota = mapa_rat.range(..prowizja.do_raty).map(|(_, r)| r.nadplata).sum();
+            let prowizja = Koszt::builder()
+                .nazwa("prowizja za nadplate")
+                .wartosc(prowizja.procent)
+                .operator(Operator::Procent)
+                .okres(Okres::Jednorazowy)
+                .build()
+                .oblicz(nadplaty, liczba_rat);
+
+            koszty.insert(prowizja.nazwa(), prowizja);
+        }
+
+        Ok(koszty)
+    }
+
+    /// Liczba rat, po ktorych LTV spada ponizej `ltv`. Kapital kredytu walutowego jest wyceniany
+    /// po biezacym kursie sprzedazy.
+    fn raty_do_ltv(dto: &Kredyt, mapa_rat: &BTreeMap<u64, Rata>, kursy: Option<&KursyWaluty>, ltv: f64) -> u64 {
+        let mut kapital_do_splaty = dto.kwota_kredytowania();
+        let kurs = |numer_raty: u64| kursy.map_or(1.0, |k| k.sprzedaz(numer_raty) / k.kupno(0));
 
-        koszty.insert("PCC".to_string(), pcc);
+        for (numer_raty, rata) in mapa_rat.iter() {
+            if kapital_do_splaty.zl() * kurs(*numer_raty) * 100.0 < dto.wartosc_hipoteki.zl() * ltv {
+                return *numer_raty;
+            }
+
+            kapital_do_splaty -= rata.kapital();
+        }
 
-        koszty
+        mapa_rat.len() as u64
     }
 }
 
@@ -92,6 +382,14 @@ impl fmt::Display for Kalkulator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Koszt nieruchomosci w banku `{}` wynosi: {:.2} zl", self.nazwa, self.calkowity_koszt_nieruchomosci)?;
 
+        if let Some(kursy) = &self.kursy {
+            writeln!(
+                f,
+                "Kredyt w walucie: {:.2}{} po kursie kupna {:.4}, raty przeliczane po kursie sprzedazy",
+                self.kapital_do_splaty_w_walucie(0), kursy.kod(), kursy.kupno(0)
+            )?;
+        }
+
         writeln!(f, "Koszty:")?;
         for (_, koszt) in self.mapa_kosztow.iter() {
             writeln!(f, "    * {}", koszt)?;
@@ -102,37 +400,132 @@ impl fmt::Display for Kalkulator {
         for (i, rata) in self.mapa_rat.iter().step_by(12) {
             writeln!(f, "    * Skladowe raty (w {}. miesiacu kredytu ({}.)): {:.2}", (i + 1) , ((i + 1)/12 + 1), rata)?;
         }
+
+        if self.struktura_splaty != StrukturaSplaty::RowneRaty {
+            if let Some((i, rata)) = self.mapa_rat.iter().next_back() {
+                writeln!(f, "    * Splata: {}, ostatnia rata (w {}. miesiacu kredytu): {:.2}", self.struktura_splaty, i + 1, rata)?;
+            }
+        }
         writeln!(f, "{}", self.nadplaty)?;
 
-        for rok in vec![3, 5, 7, 10, 15] {
+        if !self.kredytobiorcy.is_empty() {
+            writeln!(f, "Kredytobiorcy:")?;
+
+            for udzial in UdzialKredytobiorcy::podziel(self) {
+                writeln!(f, "{}", udzial)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        for rok in [3, 5, 7, 10, 15] {
             let okres = rok * 12 + 1;
             let procent_hipoteki = self.procent_hipoteki(okres);
-            let kapital = self.wartosc_hipoteki * procent_hipoteki / 100.0;
+            let kapital = self.kwota_kredytowania - self.kapital_ksiegowy(okres);
+            let ltv = self.ltv(okres).map_or_else(|| "-".to_string(), |ltv| format!("{:.2}%", ltv));
             writeln!(f, "Koszt kredytu po {} latach: {:.2}zl", rok, self.koszt_kredytu(okres))?;
-            writeln!(f, "Procent hipoteki po {} latach: {:.2}% ({:.2}zl)", rok, procent_hipoteki, kapital)?;
+            writeln!(f, "Splacony kredyt po {} latach: {:.2}% ({:.2}zl), pozostalo: {:.2}zl", rok, procent_hipoteki, kapital, self.kapital_do_splaty(okres))?;
+            writeln!(
+                f,
+                "Nieruchomosc po {} latach: wartosc {:.2}zl, kapital wlasny {:.2}zl, LTV {}, bilans {:.2}zl",
+                rok, self.wartosc_nieruchomosci(okres), self.kapital_wlasny(okres), ltv, self.bilans(okres)
+            )?;
+
+            if let Some(kursy) = &self.kursy {
+                writeln!(
+                    f,
+                    "Kredyt walutowy po {} latach: pozostalo {:.2}{}, kurs sprzedazy {:.4}, roznice kursowe {:.2}zl",
+                    rok, self.kapital_do_splaty_w_walucie(okres), kursy.kod(), kursy.sprzedaz(okres), self.roznice_kursowe(okres)
+                )?;
+            }
         }
 
-        dbg!(self.mapa_rat.values().map(|v| v.odsetki).sum::<f64>());
-        dbg!(self.mapa_rat.values().map(|v| v.kapital + v.nadplata).sum::<f64>());
+        let rrso = self.rrso().map_or_else(|| "-".to_string(), |rrso| format!("{:.2}%", rrso));
+        writeln!(f, "Calkowity koszt kredytu {:.2}zl, RRSO {}", self.koszt_kredytu(None), rrso)?;
 
-        writeln!(f, "Calkowity koszt kredytu {:.2}zl", self.koszt_kredytu(None))?;
+        if self.kursy.is_some() {
+            writeln!(f, "W tym roznice kursowe {:.2}zl", self.roznice_kursowe(u64::MAX))?;
+        }
 
-        writeln!(f, "")
-    }
-}
+        if let Some((nazwa, utrata)) = &self.doplata {
+            writeln!(f, "Doplaty z programu `{}`: {:.2}zl", nazwa, self.doplaty(u64::MAX))?;
 
-pub fn oferta(path: &str) {
-    let data = std::fs::read_to_string(path).unwrap();
-    let kredyt: Kredyt = serde_json::from_str(&data).unwrap();
-    let kalkulator = Kalkulator::new(kredyt);
+            if let Some(utrata) = utrata {
+                writeln!(f, "    * {}", utrata)?;
+            }
+        }
 
-    println!("{}", kalkulator);
+        writeln!(f)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn harmonogram_splaca_caly_kapital_co_do_grosza() {
+        for oferta in ["data/kredyt/mbank.json", "data/kredyt/pekao_sa_25_test.json", "data/kredyt/pekao_sa_25_refinansowanie.json", "data/kredyt/mbank_chf.json", "data/kredyt/mbank_budowa.json"] {
+            let kalkulator = Kalkulator::new(Kredyt::wczytaj(oferta).unwrap()).unwrap();
+            let splacony_kapital: Kwota = kalkulator.mapa_rat().values().map(|r| r.kapital()).sum();
+
+            assert_eq!(splacony_kapital, kalkulator.kwota_kredytowania);
+        }
+    }
+
+    #[test]
+    fn ltv_nieruchomosci_bez_wartosci_jest_nieokreslone() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_wartosc.json").unwrap();
+        let kalkulator = Kalkulator::new(kredyt.clone()).unwrap();
+        assert!((kalkulator.ltv(0).unwrap() - 90.0).abs() < 1e-9);
+
+        kredyt.wartosc_hipoteki = Kwota::ZERO;
+        assert_eq!(Kalkulator::new(kredyt).unwrap().ltv(0), None);
+    }
+
+    #[test]
+    fn ltv_kredytu_walutowego_po_biezacym_kursie() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/mbank_chf.json").unwrap();
+        let kalkulator = Kalkulator::new(kredyt.clone()).unwrap();
+        let mapa_rat = kalkulator.mapa_rat();
+        let w_zlotych = Kalkulator::raty_do_ltv(&kredyt, mapa_rat, None, 70.0);
+        let po_kursie = Kalkulator::raty_do_ltv(&kredyt, mapa_rat, kalkulator.kursy(), 70.0);
+
+        // frank drozeje, wiec LTV spada wolniej niz splacany kapital ksiegowy
+        assert!(po_kursie > w_zlotych);
+    }
+
+    fn bez_kosztow(oferta: &str) -> Kredyt {
+        let mut kredyt = Kredyt::wczytaj(oferta).unwrap();
+        kredyt.koszty.clear();
+        kredyt.splata = None;
 
+        kredyt
+    }
+
+    #[test]
+    fn rrso_bez_kosztow_rowne_stopie_efektywnej() {
+        // bez kosztow RRSO to oprocentowanie nominalne kapitalizowane co miesiac: (1 + 5.98% / 12)^12 - 1
+        let efektywna = ((1.0 + 0.0598 / 12.0f64).powi(12) - 1.0) * 100.0;
+        // kredyt na budowe nie ma PCC, a jedna transza na poczatku to zwykly kredyt z jedna rata odsetkowa
+        let mut transze = bez_kosztow("data/kredyt/mbank_budowa.json");
+        transze.budowa.as_mut().unwrap().inspekcja = Kwota::ZERO;
+        let mut jedna_transza = transze.clone();
+        jedna_transza.budowa.as_mut().unwrap().etapy.truncate(1);
+        jedna_transza.budowa.as_mut().unwrap().etapy[0].procent = 100.0;
+
+        for kredyt in [jedna_transza, transze] {
+            let rrso = Kalkulator::new(kredyt).unwrap().rrso().unwrap();
+            assert!((rrso - efektywna).abs() < 0.005, "RRSO {} zamiast {}", rrso, efektywna);
+        }
+    }
+
+    #[test]
+    fn rrso_poza_przedzialem_jest_nieokreslone() {
+        // oplata pobierana przy wyplacie przewyzsza kwote kredytu
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/mbank.json").unwrap();
+        kredyt.koszty.push(Koszt::builder().nazwa("oplata").wartosc(200.0).operator(Operator::Procent).okres(Okres::Jednorazowy).build());
 
-}
\ No newline at end of file
+        assert_eq!(Kalkulator::new(kredyt).unwrap().rrso(), None);
+    }
+}
@@ -3,6 +3,8 @@ use std::{fmt, ops::Deref};
 use serde::{Serialize, Deserialize};
 use typed_builder::TypedBuilder;
 
+use crate::Kwota;
+
 #[derive(Clone, Serialize, Deserialize, TypedBuilder)]
 pub struct Koszt {
     #[serde(rename = "nazwa")]
@@ -21,6 +23,11 @@ pub struct Koszt {
     #[serde(rename = "okresow")]
     #[builder(default, setter(strip_option))]
     okresow: Option<u64>,
+
+    /// Koszt jest naliczany, dopoki LTV kredytu (w procentach) nie spadnie ponizej tego progu.
+    #[serde(rename = "do_ltv", default, skip_serializing_if = "Option::is_none")]
+    #[builder(default, setter(strip_option))]
+    do_ltv: Option<f64>,
 }
 
 impl Koszt {
@@ -28,25 +35,18 @@ impl Koszt {
         self.nazwa.to_string()
     }
 
-    pub fn oblicz(&self, wartosc_bazowa: f64, okres: u64) -> KosztKoncowy {
+    pub fn oblicz(&self, wartosc_bazowa: Kwota, okres: u64) -> KosztKoncowy {
         let wartosc = match self.operator {
-            Operator::Procent => self.wartosc * wartosc_bazowa / 100.0,
-            Operator::Stala => self.wartosc
+            Operator::Procent => wartosc_bazowa * (self.wartosc / 100.0),
+            Operator::Stala => Kwota::from(self.wartosc)
         };
 
-        let wartosc = match self.okres {
-            Okres::Jednorazowy => {
-                wartosc
-            },
-            Okres::Miesieczny => {
-                wartosc * self.okresow.unwrap_or(okres) as f64
-            }
-            Okres::Roczny => {
-                wartosc * (okres / 12) as f64
-            }
-        };
+        KosztKoncowy::new(self.clone(), wartosc, okres)
+    }
 
-        KosztKoncowy::new(self.clone(), wartosc)
+    pub fn przemianuj(mut self, nazwa: impl Into<String>) -> Self {
+        self.nazwa = nazwa.into();
+        self
     }
 
     pub fn okres(&self) -> Okres {
@@ -60,6 +60,10 @@ impl Koszt {
     pub fn wartosc(&self) -> f64 {
         self.wartosc
     }
+
+    pub fn do_ltv(&self) -> Option<f64> {
+        self.do_ltv
+    }
 }
 
 impl fmt::Display for Koszt {
@@ -70,17 +74,71 @@ impl fmt::Display for Koszt {
 
 pub struct KosztKoncowy {
     data: Koszt,
-    total: f64,
+    wartosc: Kwota,
+    okres: u64,
+    od_raty: u64,
+    total: Kwota,
 }
 
 impl KosztKoncowy {
-    pub fn new(data: Koszt, total: f64) -> KosztKoncowy {
-        Self { data, total }
+    /// `wartosc` to kwota pojedynczej platnosci, `okres` to liczba miesiecy, przez ktore koszt jest naliczany.
+    pub fn new(data: Koszt, wartosc: Kwota, okres: u64) -> KosztKoncowy {
+        let mut koszt = Self { data, wartosc, okres, od_raty: 0, total: Kwota::ZERO };
+        koszt.total = koszt.do_raty(okres);
+
+        koszt
+    }
+
+    /// Koszt naliczany dopiero od raty `od_raty`, np. w nowym banku po refinansowaniu.
+    pub fn od_raty(mut self, od_raty: u64) -> Self {
+        self.od_raty = od_raty;
+        self
     }
 
-    pub fn total(&self) -> f64 {
+    /// Ta sama platnosc z przeciwnym znakiem - premia zmniejszajaca koszt kredytu.
+    pub fn premia(mut self) -> Self {
+        self.wartosc = -self.wartosc;
+        self.total = -self.total;
+        self
+    }
+
+    pub fn total(&self) -> Kwota {
         self.total
     }
+
+    /// Miesieczny odpowiednik kosztu powtarzalnego, koszty jednorazowe pomija.
+    pub fn miesiecznie(&self) -> Kwota {
+        match self.data.okres {
+            Okres::Jednorazowy => Kwota::ZERO,
+            Okres::Miesieczny => self.wartosc,
+            Okres::Roczny => self.wartosc * (1.0 / 12.0),
+        }
+    }
+
+    /// Miesieczne obciazenie w racie `numer_raty`: zero przed naliczaniem kosztu i po jego wygasnieciu.
+    pub fn miesiecznie_w_racie(&self, numer_raty: u64) -> Kwota {
+        match self.data.okres {
+            Okres::Jednorazowy => Kwota::ZERO,
+            Okres::Miesieczny => self.do_raty(numer_raty + 1) - self.do_raty(numer_raty),
+            Okres::Roczny if numer_raty >= self.od_raty && numer_raty - self.od_raty < self.okres => self.miesiecznie(),
+            Okres::Roczny => Kwota::ZERO,
+        }
+    }
+
+    /// Suma platnosci poniesionych w pierwszych `numer_raty` miesiacach.
+    pub fn do_raty(&self, numer_raty: u64) -> Kwota {
+        if numer_raty < self.od_raty {
+            return Kwota::ZERO;
+        }
+
+        let numer_raty = (numer_raty - self.od_raty).min(self.okres);
+
+        match self.data.okres {
+            Okres::Jednorazowy => self.wartosc,
+            Okres::Miesieczny => self.wartosc * numer_raty.min(self.data.okresow.unwrap_or(numer_raty)),
+            Okres::Roczny => self.wartosc * (numer_raty / 12),
+        }
+    }
 }
 
 impl Deref for KosztKoncowy {
@@ -129,4 +187,24 @@ impl fmt::Display for Okres {
             Okres::Roczny => write!(fmt, "rocznie"),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn koszt_miesieczny_naliczany_do_splaty_kredytu() {
+        let ubezpieczenie = Koszt::builder()
+            .nazwa("ubezpieczenie")
+            .wartosc(110.0)
+            .operator(Operator::Stala)
+            .okres(Okres::Miesieczny)
+            .build()
+            .oblicz(Kwota::ZERO, 100);
+
+        assert_eq!(ubezpieczenie.total(), Kwota::from(11_000.0));
+        assert_eq!(ubezpieczenie.do_raty(12), Kwota::from(1_320.0));
+        assert_eq!(ubezpieczenie.do_raty(300), Kwota::from(11_000.0));
+    }
+}
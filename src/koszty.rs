@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use crate::{Koszt, KosztKoncowy};
+use crate::{wczytaj_json, Blad, Koszt, KosztKoncowy, Kwota};
 
 use serde::{Serialize, Deserialize};
 
@@ -12,22 +12,20 @@ pub struct Koszty {
 }
 
 impl Koszty {
-    pub fn oblicz(&self) -> BTreeMap<String, KosztKoncowy> {
-        self.koszty.iter().map(|k| (k.nazwa(), k.oblicz(0.0, self.okres))).collect()
+    pub fn wczytaj(path: &str) -> Result<Self, Blad> {
+        wczytaj_json(path)
     }
-}
 
-pub fn koszty(path: &str) {
-    let data = std::fs::read_to_string(path).unwrap();
-    let koszty_mieszkania: Koszty = serde_json::from_str(&data).unwrap();
-    let koszty = koszty_mieszkania.oblicz();
-    let total: f64 = koszty.iter().map(|(_, v)| v.total()).sum();
-
-    println!("Koszt utrzymania `{}` wynosi: {} zl", koszty_mieszkania.nazwa, total);
+    pub fn nazwa(&self) -> &str {
+        &self.nazwa
+    }
 
-    for (_, koszt) in koszty.iter() {
-        println!("    * {}", koszt);
+    pub fn oblicz(&self) -> BTreeMap<String, KosztKoncowy> {
+        self.koszty.iter().map(|k| (k.nazwa(), k.oblicz(Kwota::ZERO, self.okres))).collect()
     }
 
-    println!("");
-}
\ No newline at end of file
+    /// Staly miesieczny koszt utrzymania, bez kosztow jednorazowych.
+    pub fn miesiecznie(&self) -> Kwota {
+        self.oblicz().values().map(|k| k.miesiecznie()).sum()
+    }
+}
@@ -1,16 +1,19 @@
-use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use typed_builder::TypedBuilder;
 
-use crate::{ Koszt, Operator, Okres, KosztKoncowy, Nadplaty };
+use crate::{ wczytaj_json, Blad, Budowa, Doplata, Koszt, Kredytobiorca, Premia, Produkt, Kwota, Nadplaty, Refinansowanie, StrukturaSplaty, Waluta, WarunekMarzy, ZmianaMarzy, ZasadyZaokraglen, ZmianaWartosci, Indeks };
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, TypedBuilder)]
 pub struct Kredyt {
     #[serde(rename = "nazwa")]
+    #[builder(setter(into))]
     pub nazwa: String,
 
     #[serde(rename = "wartosc_hipoteki")]
-    pub wartosc_hipoteki: f64,
+    pub wartosc_hipoteki: Kwota,
 
     #[serde(rename = "wklad_wlasny")]
     pub wklad_wlasny: f64,
@@ -22,133 +25,323 @@ pub struct Kredyt {
     pub oprocentowanie: f64,
 
     #[serde(rename = "koszty")]
+    #[builder(default)]
     pub koszty: Vec<Koszt>,
 
     #[serde(rename = "nadplaty")]
+    #[builder(default)]
     pub nadplaty: Nadplaty,
 
-    #[serde(rename = "splata")]
-    pub splata: Splata,
+    #[serde(rename = "kredytobiorcy", default)]
+    #[builder(default)]
+    pub kredytobiorcy: Vec<Kredytobiorca>,
+
+    #[serde(rename = "splata", default)]
+    #[builder(default)]
+    pub splata: Option<Splata>,
+
+    #[serde(rename = "zaokraglenie", default)]
+    #[builder(default)]
+    pub zaokraglenie: ZasadyZaokraglen,
+
+    #[serde(rename = "zmiany_oprocentowania", default)]
+    #[builder(default)]
+    pub zmiany_oprocentowania: Vec<ZmianaOprocentowania>,
+
+    #[serde(rename = "zmiany_marzy", default)]
+    #[builder(default)]
+    pub zmiany_marzy: Vec<ZmianaMarzy>,
+
+    #[serde(rename = "warunki_marzy", default)]
+    #[builder(default)]
+    pub warunki_marzy: Vec<WarunekMarzy>,
+
+    #[serde(rename = "premie", default)]
+    #[builder(default)]
+    pub premie: Vec<Premia>,
+
+    /// Produkty sprzedazy wiazanej - ich oplaty sa kosztami kredytu, a obnizki marzy warunkami marzy.
+    #[serde(rename = "produkty", default)]
+    #[builder(default)]
+    pub produkty: Vec<Produkt>,
+
+    #[serde(rename = "zmiana_wartosci", default)]
+    #[builder(default)]
+    pub zmiana_wartosci: ZmianaWartosci,
+
+    #[serde(rename = "prowizja_za_nadplate", default)]
+    #[builder(default, setter(strip_option))]
+    pub prowizja_za_nadplate: Option<ProwizjaZaNadplate>,
+
+    #[serde(rename = "waluta", default)]
+    #[builder(default, setter(strip_option))]
+    pub waluta: Option<Waluta>,
+
+    /// Rata podana w ofercie banku - sluzy do sprawdzenia deklarowanego oprocentowania.
+    #[serde(rename = "rata_banku", default)]
+    #[builder(default, setter(strip_option))]
+    pub rata_banku: Option<Kwota>,
+
+    #[serde(rename = "struktura_splaty", default)]
+    #[builder(default)]
+    pub struktura_splaty: StrukturaSplaty,
+
+    #[serde(rename = "budowa", default)]
+    #[builder(default, setter(strip_option))]
+    pub budowa: Option<Budowa>,
+
+    #[serde(rename = "doplata", default)]
+    #[builder(default, setter(strip_option))]
+    pub doplata: Option<Doplata>,
+
+    #[serde(rename = "refinansowanie", default)]
+    #[builder(default, setter(strip_option))]
+    pub refinansowanie: Option<Refinansowanie>,
+}
+
+/// Prowizja od nadplat dokonanych przed rata `do_raty`, w procentach nadplaconej kwoty.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProwizjaZaNadplate {
+    #[serde(rename = "procent")]
+    pub procent: f64,
+
+    #[serde(rename = "do_raty")]
+    pub do_raty: u64,
+}
+
+/// Nowe oprocentowanie obowiazujace od raty `od`, np. po okresie stopy stalej.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ZmianaOprocentowania {
+    #[serde(rename = "od")]
+    pub od: u64,
+
+    #[serde(rename = "oprocentowanie")]
+    pub oprocentowanie: f64,
 }
 
 impl Kredyt {
-    pub fn koszt_nieruchomosci(&self) -> BTreeMap<String, KosztKoncowy> {
-        let mut koszty: BTreeMap<String, KosztKoncowy> = self.koszty.iter().map(|k| (k.nazwa(), k.oblicz(self.wartosc_hipoteki - self.wartosc_hipoteki * self.wklad_wlasny / 100.0, self.okres_kredytowania))).collect();
-        let pcc = self.wartosc_hipoteki * 2.0 / 100.0;
-        let pcc = KosztKoncowy::new(
-                Koszt::builder()
-                    .nazwa("PCC")
-                    .wartosc(2.0)
-                    .operator(Operator::Procent)
-                    .okres(Okres::Jednorazowy)
-                    .build(),
-                pcc
-            );
-
-        let rata = self.rata_rowna();
-        let raty = rata * self.okres_kredytowania as f64;
-        let raty = KosztKoncowy::new(
-            Koszt::builder()
-                .nazwa("Raty")
-                .wartosc(rata)
-                .operator(Operator::Stala)
-                .okres(Okres::Miesieczny)
-                .build(),
-            raty
-        );
-
-        koszty.insert("PCC".to_string(), pcc);
-        koszty.insert("Raty".to_string(), raty);
-
-        koszty
-    }
-
-    pub fn rata_rowna(&self) -> f64 {
-        let kwota_kredytu = self.wartosc_hipoteki - self.wartosc_hipoteki * self.wklad_wlasny / 100.0;
-        let liczba_rat_rocznie = 12.0;
-        let oprocentowanie = self.oprocentowanie / 100.0;
-
-        kwota_kredytu * oprocentowanie / (liczba_rat_rocznie * (1.0 - (liczba_rat_rocznie / (liczba_rat_rocznie + oprocentowanie)).powf(self.okres_kredytowania as f64)))
+    /// Wczytuje oferte z pliku, uwzgledniajac oferte bazowa wskazana w polu `rozszerza`.
+    pub fn wczytaj(path: &str) -> Result<Self, Blad> {
+        let path = Path::new(path);
+        let oferta = wczytaj_oferte(path, &mut Vec::new())?;
+        let mut kredyt: Self = serde_json::from_value(oferta).map_err(|blad| Blad::Json(path.to_path_buf(), blad))?;
+
+        if let Some(indeks) = kredyt.zmiana_wartosci.indeks.as_deref() {
+            kredyt.zmiana_wartosci.zmiany_roczne = Indeks::wczytaj(indeks)?.zmiany_roczne;
+        }
+
+        if let Some(waluta) = kredyt.waluta.as_mut() {
+            waluta.wczytaj_kursy()?;
+        }
+
+        kredyt.sprawdz()?;
+        Ok(kredyt)
     }
-}
 
-#[derive(Serialize, Deserialize)]
-pub struct Rata {
-    #[serde(rename = "kapital")]
-    kapital: f64,
+    /// Sprawdza spojnosc oferty, zanim zostanie policzony harmonogram.
+    pub fn sprawdz(&self) -> Result<(), Blad> {
+        let udzialy: f64 = self.kredytobiorcy.iter().map(|k| k.udzial).sum();
 
-    #[serde(rename = "odsetki")]
-    odsetki: f64,
+        if !self.kredytobiorcy.is_empty() && (udzialy - 100.0).abs() > 1e-9 {
+            return Err(Blad::Dane(format!("udzialy kredytobiorcow oferty `{}` sumuja sie do {}%", self.nazwa, udzialy)));
+        }
 
-    #[serde(rename = "delta_kapital")]
-    delta_kapital: f64,
+        if let Some(budowa) = &self.budowa {
+            budowa.sprawdz()?;
+        }
 
-    #[serde(rename = "delta_odsetki")]
-    delta_odsetki: f64,
+        for premia in self.premie.iter() {
+            premia.okres_warunku(self, self.okres_kredytowania)?;
+        }
 
-    #[serde(rename = "operator")]
-    operator: Operator,
+        // koszty, produkty i premie trafiaja do jednej mapy kosztow wg nazwy
+        let mut nazwy = vec!["PCC".to_string(), "prowizja za nadplate".to_string()];
+        let koszty = self.koszty.iter().map(|k| k.nazwa())
+            .chain(self.produkty.iter().map(|p| p.nazwa.clone()))
+            .chain(self.premie.iter().map(|p| p.koszt.nazwa()));
 
-    #[serde(rename = "okres")]
-    okres: Okres,
+        for nazwa in koszty {
+            if nazwy.contains(&nazwa) {
+                return Err(Blad::Dane(format!("oferta `{}` ma wiecej niz jeden koszt o nazwie `{}`", self.nazwa, nazwa)));
+            }
 
-    #[serde(rename = "nadplata")]
-    nadplata: Option<f64>,
-}
+            nazwy.push(nazwa);
+        }
+
+        Ok(())
+    }
+
+    pub fn kwota_kredytowania(&self) -> Kwota {
+        self.wartosc_hipoteki - self.wartosc_hipoteki * (self.wklad_wlasny / 100.0)
+    }
 
-impl Rata {
-    pub fn oblicz(&self, okres_kredytowania: u64) -> f64 {
-        // TODO
-        // let nadplata_w_stosunku_okresu = self.nadplata / okres_kredytowania as f64;
+    /// Nadplaty wspolne i wlasne nadplaty wszystkich kredytobiorcow.
+    pub fn wszystkie_nadplaty(&self) -> Nadplaty {
+        let mut nadplaty = self.nadplaty.clone();
 
-        (0..okres_kredytowania).map(|numer_raty| {
-            let kapital = self.kapital + self.delta_kapital * numer_raty as f64;
-            let odsetki = self.odsetki + self.delta_odsetki * numer_raty as f64;
+        for nadplata in self.kredytobiorcy.iter().flat_map(|k| k.nadplaty.iter()) {
+            nadplaty.dodaj(nadplata.clone());
+        }
 
-            debug_assert!(odsetki >= 0.0);
+        nadplaty
+    }
 
-            kapital + odsetki
-        })
-        .sum()
+    /// Oprocentowanie w racie `numer_raty`: bazowe (z `zmiany_oprocentowania`) powiekszone
+    /// o obowiazujace zmiany marzy i podwyzki za niespelnione warunki.
+    pub fn oprocentowanie_w_racie(&self, numer_raty: u64) -> f64 {
+        let bazowe = self.zmiany_oprocentowania.iter()
+            .filter(|z| z.od <= numer_raty)
+            .max_by_key(|z| z.od)
+            .map_or(self.oprocentowanie, |z| z.oprocentowanie);
+
+        bazowe
+            + self.zmiany_marzy.iter().filter(|z| z.obowiazuje(numer_raty)).map(|z| z.zmiana).sum::<f64>()
+            + self.wszystkie_warunki_marzy().iter().filter(|w| !w.spelniony(numer_raty)).map(|w| w.podwyzka_marzy).sum::<f64>()
     }
 
-    pub fn wartosc(&self) -> f64 {
-        self.kapital + self.odsetki + self.nadplata.unwrap_or_default()
+    /// Warunki marzy oferty i obnizki marzy za produkty.
+    pub fn wszystkie_warunki_marzy(&self) -> Vec<WarunekMarzy> {
+        self.warunki_marzy.iter().cloned()
+            .chain(self.produkty.iter().filter_map(|p| p.warunek_marzy()))
+            .collect()
+    }
+
+    /// Raty, w ktorych moze zmienic sie oprocentowanie.
+    pub fn zmiany_oprocentowania_w_ratach(&self) -> Vec<u64> {
+        let mut raty: Vec<u64> = self.zmiany_oprocentowania.iter().map(|z| z.od)
+            .chain(self.zmiany_marzy.iter().flat_map(|z| std::iter::once(z.od).chain(z.to)))
+            .chain(self.wszystkie_warunki_marzy().iter().flat_map(|w| std::iter::once(w.spelniony_od).chain(w.spelniony_do)))
+            .collect();
+        raty.sort_unstable();
+        raty.dedup();
+
+        raty
+    }
+}
+
+/// Oferta moze rozszerzac inna oferte: `"rozszerza": "pekao_sa_25.json"` (sciezka wzgledem pliku).
+/// Pola oferty nadpisuja pola bazowej, koszty sa laczone po `nazwa` (`"usun": true` usuwa koszt),
+/// a lista nadplat zastepowana w calosci. `wczytane` to pliki juz wczytane w tym lancuchu rozszerzen.
+fn wczytaj_oferte(path: &Path, wczytane: &mut Vec<PathBuf>) -> Result<Value, Blad> {
+    let sciezka = path.canonicalize().map_err(|blad| Blad::Plik(path.to_path_buf(), blad))?;
+    if wczytane.contains(&sciezka) {
+        return Err(Blad::CyklRozszerzen(path.to_path_buf()));
+    }
+    wczytane.push(sciezka);
+
+    let mut oferta: Value = wczytaj_json(path)?;
+    rozwiaz_sciezki(&mut oferta, path);
+
+    match oferta.as_object_mut().and_then(|o| o.remove("rozszerza")) {
+        Some(Value::String(bazowa)) => {
+            let mut bazowa = wczytaj_oferte(&path.with_file_name(bazowa), wczytane)?;
+            nadpisz(&mut bazowa, oferta);
+            Ok(bazowa)
+        },
+        _ => Ok(oferta)
+    }
+}
+
+/// Sciezki do plikow dolaczonych do oferty sa podawane wzgledem pliku oferty, w ktorym wystepuja.
+fn rozwiaz_sciezki(oferta: &mut Value, path: &Path) {
+    for pole in ["/zmiana_wartosci/indeks", "/waluta/kursy"].iter() {
+        if let Some(Value::String(sciezka)) = oferta.pointer_mut(pole) {
+            *sciezka = path.with_file_name(&*sciezka).to_string_lossy().into_owned();
+        }
     }
 }
 
+fn nadpisz(bazowa: &mut Value, oferta: Value) {
+    match (bazowa, oferta) {
+        (Value::Object(bazowa), Value::Object(oferta)) => {
+            for (klucz, wartosc) in oferta {
+                match (klucz.as_str(), bazowa.get_mut(&klucz)) {
+                    ("koszty", Some(Value::Array(koszty))) => nadpisz_koszty(koszty, wartosc),
+                    // wariant enuma z `typ` zastepuje bazowy w calosci, aby nie dziedziczyc jego pol
+                    (_, Some(pole @ Value::Object(_))) if wartosc.get("typ").is_none() => nadpisz(pole, wartosc),
+                    _ => { bazowa.insert(klucz, wartosc); },
+                }
+            }
+        },
+        (bazowa, oferta) => *bazowa = oferta,
+    }
+}
+
+fn nadpisz_koszty(koszty: &mut Vec<Value>, nadpisane: Value) {
+    let nazwa = |koszt: &Map<String, Value>| koszt.get("nazwa").cloned();
+
+    for koszt in nadpisane.as_array().cloned().unwrap_or_default() {
+        let koszt = match koszt {
+            Value::Object(koszt) => koszt,
+            _ => continue,
+        };
+        let pozycja = koszty.iter().position(|k| k.as_object().and_then(nazwa) == nazwa(&koszt));
+        let usun = koszt.get("usun").and_then(Value::as_bool).unwrap_or(false);
+
+        match pozycja {
+            Some(i) if usun => { koszty.remove(i); },
+            Some(i) => nadpisz(&mut koszty[i], Value::Object(koszt)),
+            None if usun => {},
+            None => koszty.push(Value::Object(koszt)),
+        }
+    }
+}
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Splata {
     #[serde(flatten)]
     wartosc: Koszt
 }
 
-// pub fn oferta(path: &str) {
-//     let data = std::fs::read_to_string(path).unwrap();
-//     let kredyt: Kredyt = serde_json::from_str(&data).unwrap();
-//     let koszty = kredyt.koszt_nieruchomosci();
-//     let total: f64 = koszty.iter().map(|(_, v)| v.total()).sum();
-//     let koszt_kredytu = total - kredyt.wartosc_hipoteki;
-
-//     println!("Koszt nieruchomosci w banku `{}` wynosi: {} zl", kredyt.nazwa, total);
-
-//     for (_, koszt) in koszty.iter() {
-//         println!("    * {}", koszt);
-//     }
-
-//     let miesieczne_okresowe: f64 = koszty.iter()
-//         .filter(|(_, v)| v.okres() == Okres::Miesieczny)
-//         .map(|(_, v)| v.wartosc())
-//         .sum();
-
-//     let miesieczne_stale: f64 = koszty.iter()
-//         .filter(|(_, v)| v.okres() == Okres::Miesieczny && v.okresow().is_none())
-//         .map(|(_, v)| v.wartosc())
-//         .sum();
-
-//     println!("Maksymalne miesięczne zobowiązanie: {}zl", miesieczne_okresowe);
-//     println!("Standardowe miesięczne zobowiązanie: {}zl", miesieczne_stale);
-//     println!("Koszt kredytu: {}zl", koszt_kredytu);
-//     println!("");
-// }
+impl Splata {
+    pub fn wartosc(&self) -> &Koszt {
+        &self.wartosc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oferta_dziedziczy_pola_oferty_bazowej() {
+        let bazowa = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_test.json").unwrap();
+
+        assert_eq!(kredyt.wartosc_hipoteki, bazowa.wartosc_hipoteki);
+        assert_eq!(kredyt.koszty.len(), bazowa.koszty.len() + 1);
+        assert_eq!(kredyt.koszty.iter().find(|k| k.nazwa() == "pomostowe").map(|k| k.wartosc()), Some(346.56));
+    }
+
+    #[test]
+    fn cykl_rozszerzen_jest_bledem() {
+        let katalog = std::env::temp_dir().join(format!("hipoteka_cykl_{}", std::process::id()));
+        std::fs::create_dir_all(&katalog).unwrap();
+        std::fs::write(katalog.join("a.json"), r#"{"rozszerza": "b.json"}"#).unwrap();
+        std::fs::write(katalog.join("b.json"), r#"{"rozszerza": "a.json"}"#).unwrap();
+
+        let wynik = Kredyt::wczytaj(katalog.join("a.json").to_str().unwrap());
+        std::fs::remove_dir_all(&katalog).unwrap();
+
+        assert!(matches!(wynik, Err(Blad::CyklRozszerzen(_))));
+    }
+
+    #[test]
+    fn indeks_wczytywany_wzgledem_pliku_oferty() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_wartosc.json").unwrap();
+        let indeks = Indeks::wczytaj("data/indeksy/ceny_mieszkan.json").unwrap();
+
+        assert_eq!(kredyt.zmiana_wartosci.zmiany_roczne, indeks.zmiany_roczne);
+    }
+
+    #[test]
+    fn zmiana_wariantu_nie_dziedziczy_pol_bazowego() {
+        let mut bazowa = serde_json::json!({"struktura_splaty": {"typ": "balon", "procent": 30}});
+        nadpisz(&mut bazowa, serde_json::json!({"struktura_splaty": {"typ": "tylko_odsetki", "miesiecy": 24}}));
+
+        assert_eq!(bazowa, serde_json::json!({"struktura_splaty": {"typ": "tylko_odsetki", "miesiecy": 24}}));
+
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_balon_odsetki.json").unwrap();
+        assert!(kredyt.struktura_splaty == StrukturaSplaty::TylkoOdsetki { miesiecy: Some(24) });
+    }
+}
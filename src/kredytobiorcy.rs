@@ -0,0 +1,191 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{kalkulator::Kalkulator, Kwota, Nadplaty};
+
+/// Kredytobiorca z udzialem we wlasnosci nieruchomosci (w procentach). Raty, koszty i wspolne
+/// nadplaty oferty sa dzielone wg udzialow, a `nadplaty` kredytobiorcy sa jego wlasnymi wplatami.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Kredytobiorca {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    #[serde(rename = "udzial")]
+    pub udzial: f64,
+
+    #[serde(rename = "nadplaty", default)]
+    pub nadplaty: Nadplaty,
+}
+
+/// Czesc jednej raty przypadajaca na kredytobiorce.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RataKredytobiorcy {
+    pub rata: Kwota,
+    pub nadplata_wspolna: Kwota,
+    pub nadplata_wlasna: Kwota,
+}
+
+impl RataKredytobiorcy {
+    pub fn wplata(&self) -> Kwota {
+        self.rata + self.nadplata_wspolna + self.nadplata_wlasna
+    }
+}
+
+pub struct UdzialKredytobiorcy {
+    pub nazwa: String,
+    pub udzial: f64,
+    /// Czesc kazdej raty i nadplat przypadajaca na kredytobiorce.
+    pub harmonogram: BTreeMap<u64, RataKredytobiorcy>,
+    pub raty: Kwota,
+    pub koszty: BTreeMap<String, Kwota>,
+    pub nadplaty_wspolne: Kwota,
+    pub nadplaty_wlasne: Kwota,
+    /// Wlasne nadplaty ponad udzial we wszystkich wlasnych nadplatach - kwota do zwrotu
+    /// przez pozostalych kredytobiorcow (ujemna, gdy to ten kredytobiorca jest winien).
+    pub do_rozliczenia: Kwota,
+}
+
+impl UdzialKredytobiorcy {
+    /// Podzial platnosci kalkulatora miedzy kredytobiorcow. Faktycznie nadplacona kwota (ograniczona
+    /// saldem kredytu) jest dzielona proporcjonalnie do zaplanowanych nadplat wspolnych i wlasnych.
+    pub fn podziel(kalkulator: &Kalkulator) -> Vec<UdzialKredytobiorcy> {
+        let kredytobiorcy = kalkulator.kredytobiorcy();
+        let udzialy: Vec<f64> = kredytobiorcy.iter().map(|k| k.udzial).collect();
+        let mut harmonogramy = vec![BTreeMap::new(); kredytobiorcy.len()];
+
+        for (&numer_raty, rata) in kalkulator.mapa_rat().iter() {
+            let wspolne = kalkulator.nadplaty().wartosc(numer_raty);
+            let wlasne: Vec<Kwota> = kredytobiorcy.iter().map(|k| k.nadplaty.wartosc(numer_raty)).collect();
+            let zaplanowane = wspolne + wlasne.iter().copied().sum::<Kwota>();
+            let nadplata = rata.nadplata + rata.roznice_kursowe_nadplaty;
+            let czesc = |kwota: Kwota| if zaplanowane > Kwota::ZERO { nadplata * (kwota.zl() / zaplanowane.zl()) } else { Kwota::ZERO };
+            let nadplaty_wlasne: Vec<Kwota> = wlasne.into_iter().map(czesc).collect();
+            let nadplata_wspolna = nadplata - nadplaty_wlasne.iter().copied().sum::<Kwota>();
+
+            let raty = podziel_kwote(rata.wartosc(), &udzialy);
+            let nadplaty_wspolne = podziel_kwote(nadplata_wspolna, &udzialy);
+
+            for (i, harmonogram) in harmonogramy.iter_mut().enumerate() {
+                harmonogram.insert(numer_raty, RataKredytobiorcy {
+                    rata: raty[i],
+                    nadplata_wspolna: nadplaty_wspolne[i],
+                    nadplata_wlasna: nadplaty_wlasne[i],
+                });
+            }
+        }
+
+        let suma = |harmonogram: &BTreeMap<u64, RataKredytobiorcy>, pole: fn(&RataKredytobiorcy) -> Kwota| harmonogram.values().map(pole).sum::<Kwota>();
+        let wszystkie_wlasne: Kwota = harmonogramy.iter().map(|h| suma(h, |r| r.nadplata_wlasna)).sum();
+        let udzialy_wlasnych = podziel_kwote(wszystkie_wlasne, &udzialy);
+        let koszty: Vec<(&String, Vec<Kwota>)> = kalkulator.mapa_kosztow().iter()
+            .map(|(nazwa, koszt)| (nazwa, podziel_kwote(koszt.total(), &udzialy)))
+            .collect();
+
+        kredytobiorcy.iter().zip(harmonogramy).enumerate()
+            .map(|(i, (k, harmonogram))| {
+                let nadplaty_wlasne = suma(&harmonogram, |r| r.nadplata_wlasna);
+
+                UdzialKredytobiorcy {
+                    nazwa: k.nazwa.clone(),
+                    udzial: k.udzial,
+                    raty: suma(&harmonogram, |r| r.rata),
+                    koszty: koszty.iter().map(|(nazwa, czesci)| ((*nazwa).clone(), czesci[i])).collect(),
+                    nadplaty_wspolne: suma(&harmonogram, |r| r.nadplata_wspolna),
+                    nadplaty_wlasne,
+                    do_rozliczenia: nadplaty_wlasne - udzialy_wlasnych[i],
+                    harmonogram,
+                }
+            })
+            .collect()
+    }
+
+    pub fn wplacono(&self) -> Kwota {
+        self.raty + self.koszty.values().copied().sum::<Kwota>() + self.nadplaty_wspolne + self.nadplaty_wlasne
+    }
+}
+
+/// Podzial kwoty wg udzialow w procentach. Ostatni kredytobiorca dostaje reszte, aby czesci
+/// sumowaly sie do kwoty co do grosza.
+fn podziel_kwote(kwota: Kwota, udzialy: &[f64]) -> Vec<Kwota> {
+    let mut czesci: Vec<Kwota> = udzialy.iter().map(|udzial| kwota * (udzial / 100.0)).collect();
+
+    if let Some((ostatnia, poprzednie)) = czesci.split_last_mut() {
+        *ostatnia = kwota - poprzednie.iter().copied().sum::<Kwota>();
+    }
+
+    czesci
+}
+
+impl fmt::Display for UdzialKredytobiorcy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    * {} ({:.2}%): wplacono {:.2}zl", self.nazwa, self.udzial, self.wplacono())?;
+        writeln!(f, "        * raty: {:.2}zl", self.raty)?;
+
+        for (nazwa, koszt) in self.koszty.iter() {
+            writeln!(f, "        * {}: {:.2}zl", nazwa, koszt)?;
+        }
+
+        writeln!(f, "        * nadplaty wspolne: {:.2}zl, wlasne: {:.2}zl", self.nadplaty_wspolne, self.nadplaty_wlasne)?;
+        write!(f, "        * do rozliczenia: {:.2}zl", self.do_rozliczenia)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Kredyt;
+
+    #[test]
+    fn raty_dzielone_wg_udzialow_a_wlasne_nadplaty_rozliczane() {
+        let kalkulator = Kalkulator::new(Kredyt::wczytaj("data/kredyt/pekao_sa_25_wspolny.json").unwrap()).unwrap();
+        let udzialy = UdzialKredytobiorcy::podziel(&kalkulator);
+        let (anna, piotr) = (&udzialy[0], &udzialy[1]);
+        let rata = kalkulator.mapa_rat()[&0].wartosc();
+
+        assert_eq!(anna.harmonogram[&0].rata, rata * 0.6);
+        assert_eq!(piotr.harmonogram[&0].rata, rata * 0.4);
+        assert_eq!(anna.harmonogram[&24].nadplata_wlasna, Kwota::from(20_000.0));
+        assert_eq!(piotr.harmonogram[&24].nadplata_wlasna, Kwota::ZERO);
+        assert_eq!(anna.do_rozliczenia, Kwota::from(8_000.0));
+        assert_eq!(anna.do_rozliczenia, -piotr.do_rozliczenia);
+    }
+
+    #[test]
+    fn udzialy_nie_sumujace_sie_do_100_procent_sa_bledem() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_wspolny.json").unwrap();
+        kredyt.kredytobiorcy[1].udzial = 30.0;
+
+        assert!(Kalkulator::new(kredyt).is_err());
+    }
+
+    #[test]
+    fn udzialy_sumuja_sie_co_do_grosza() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_wspolny.json").unwrap();
+        kredyt.kredytobiorcy[0].udzial = 50.0;
+        kredyt.kredytobiorcy[1].udzial = 50.0;
+        let kalkulator = Kalkulator::new(kredyt).unwrap();
+        let udzialy = UdzialKredytobiorcy::podziel(&kalkulator);
+
+        assert_eq!(podziel_kwote(Kwota::z_groszy(3), &[50.0, 50.0]).into_iter().sum::<Kwota>(), Kwota::z_groszy(3));
+        assert!(kalkulator.mapa_rat().values().any(|r| r.wartosc().grosze() % 2 == 1));
+
+        for (numer_raty, rata) in kalkulator.mapa_rat().iter() {
+            let raty: Kwota = udzialy.iter().map(|u| u.harmonogram[numer_raty].rata).sum();
+            let nadplaty: Kwota = udzialy.iter().map(|u| u.harmonogram[numer_raty].nadplata_wspolna + u.harmonogram[numer_raty].nadplata_wlasna).sum();
+
+            assert_eq!(raty, rata.wartosc(), "rata {}", numer_raty);
+            assert_eq!(nadplaty, rata.nadplata, "nadplata w racie {}", numer_raty);
+        }
+
+        for (nazwa, koszt) in kalkulator.mapa_kosztow().iter() {
+            assert_eq!(udzialy.iter().map(|u| u.koszty[nazwa]).sum::<Kwota>(), koszt.total(), "koszt {}", nazwa);
+        }
+
+        let wplacono: Kwota = udzialy.iter().map(|u| u.wplacono()).sum();
+        let raty: Kwota = kalkulator.mapa_rat().values().map(|r| r.wartosc() + r.nadplata).sum();
+        let koszty: Kwota = kalkulator.mapa_kosztow().values().map(|k| k.total()).sum();
+        assert_eq!(wplacono, raty + koszty);
+        assert_eq!(udzialy.iter().map(|u| u.do_rozliczenia).sum::<Kwota>(), Kwota::ZERO);
+    }
+}
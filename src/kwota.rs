@@ -0,0 +1,189 @@
+use std::{fmt, iter::Sum, ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign}};
+
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+
+/// Kwota pieniezna przechowywana w groszach.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Kwota(i64);
+
+impl Kwota {
+    pub const ZERO: Kwota = Kwota(0);
+
+    pub fn z_groszy(grosze: i64) -> Self {
+        Self(grosze)
+    }
+
+    pub fn zaokraglij(zl: f64, zaokraglenie: Zaokraglenie) -> Self {
+        Self(zaokraglenie.zaokraglij(zl * 100.0) as i64)
+    }
+
+    pub fn grosze(self) -> i64 {
+        self.0
+    }
+
+    pub fn zl(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    /// Iloczyn kwoty i wspolczynnika, zaokraglony do pelnych groszy.
+    pub fn razy(self, wspolczynnik: f64, zaokraglenie: Zaokraglenie) -> Self {
+        Self(zaokraglenie.zaokraglij(self.0 as f64 * wspolczynnik) as i64)
+    }
+
+    pub fn procent(self, procent: f64, zaokraglenie: Zaokraglenie) -> Self {
+        self.razy(procent / 100.0, zaokraglenie)
+    }
+}
+
+impl From<f64> for Kwota {
+    fn from(zl: f64) -> Self {
+        Self::zaokraglij(zl, Zaokraglenie::default())
+    }
+}
+
+impl From<Kwota> for f64 {
+    fn from(kwota: Kwota) -> Self {
+        kwota.zl()
+    }
+}
+
+impl Add for Kwota {
+    type Output = Kwota;
+
+    fn add(self, rhs: Kwota) -> Kwota {
+        Kwota(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Kwota {
+    fn add_assign(&mut self, rhs: Kwota) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Kwota {
+    type Output = Kwota;
+
+    fn sub(self, rhs: Kwota) -> Kwota {
+        Kwota(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Kwota {
+    fn sub_assign(&mut self, rhs: Kwota) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Kwota {
+    type Output = Kwota;
+
+    fn neg(self) -> Kwota {
+        Kwota(-self.0)
+    }
+}
+
+impl Mul<f64> for Kwota {
+    type Output = Kwota;
+
+    fn mul(self, rhs: f64) -> Kwota {
+        self.razy(rhs, Zaokraglenie::default())
+    }
+}
+
+impl Mul<u64> for Kwota {
+    type Output = Kwota;
+
+    fn mul(self, rhs: u64) -> Kwota {
+        Kwota(self.0 * rhs as i64)
+    }
+}
+
+impl Sum for Kwota {
+    fn sum<I: Iterator<Item = Kwota>>(iter: I) -> Kwota {
+        iter.fold(Kwota::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Kwota> for Kwota {
+    fn sum<I: Iterator<Item = &'a Kwota>>(iter: I) -> Kwota {
+        iter.copied().sum()
+    }
+}
+
+impl fmt::Display for Kwota {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(_) => fmt::Display::fmt(&self.zl(), f),
+            None => write!(f, "{:>1$.2}", self.zl(), f.width().unwrap_or(0)),
+        }
+    }
+}
+
+impl Serialize for Kwota {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.zl())
+    }
+}
+
+impl<'de> Deserialize<'de> for Kwota {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Kwota::from)
+    }
+}
+
+/// Sposob zaokraglania ulamkow grosza.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Zaokraglenie {
+    /// Polowki w strone od zera.
+    #[default]
+    Matematyczne,
+    /// Polowki do parzystej.
+    Bankowe,
+    WGore,
+    WDol,
+}
+
+impl Zaokraglenie {
+    fn zaokraglij(self, grosze: f64) -> f64 {
+        match self {
+            Zaokraglenie::Matematyczne => grosze.round(),
+            Zaokraglenie::Bankowe => grosze.round_ties_even(),
+            Zaokraglenie::WGore => grosze.ceil(),
+            Zaokraglenie::WDol => grosze.floor(),
+        }
+    }
+}
+
+/// Co dzieje sie z roznica wynikajaca z zaokraglen rat.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Korekta {
+    /// Rata jest stala, a ostatnia rata splaca pozostaly kapital.
+    #[default]
+    OstatniaRata,
+    /// Rata jest przeliczana co miesiac z pozostalego kapitalu.
+    CoMiesiac,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ZasadyZaokraglen {
+    #[serde(rename = "metoda", default)]
+    pub metoda: Zaokraglenie,
+
+    #[serde(rename = "korekta", default)]
+    pub korekta: Korekta,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zaokraglenie_bankowe_polowki_do_parzystej() {
+        assert_eq!(Kwota::z_groszy(5).razy(0.5, Zaokraglenie::Bankowe), Kwota::z_groszy(2));
+        assert_eq!(Kwota::z_groszy(7).razy(0.5, Zaokraglenie::Bankowe), Kwota::z_groszy(4));
+        assert_eq!(Kwota::z_groszy(5).razy(0.5, Zaokraglenie::Matematyczne), Kwota::z_groszy(3));
+    }
+}
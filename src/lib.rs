@@ -0,0 +1,90 @@
+//! Kalkulator kredytow hipotecznych.
+//!
+//! Oferte mozna wczytac z pliku (`Kredyt::wczytaj`) albo zbudowac w kodzie:
+//!
+//! ```
+//! use hipoteka::{Kalkulator, Koszt, Kredyt, Kwota, Nadplata, Nadplaty, Okres, Operator};
+//!
+//! let kredyt = Kredyt::builder()
+//!     .nazwa("Bank")
+//!     .wartosc_hipoteki(Kwota::from(700_000.0))
+//!     .wklad_wlasny(20.0)
+//!     .okres_kredytowania(300)
+//!     .oprocentowanie(7.0)
+//!     .koszty(vec![
+//!         Koszt::builder().nazwa("prowizja").wartosc(2.0).operator(Operator::Procent).okres(Okres::Jednorazowy).build(),
+//!     ])
+//!     .nadplaty(Nadplaty::new(vec![
+//!         Nadplata::builder().wartosc(Kwota::from(1000.0)).from(0).to(60).build(),
+//!     ]))
+//!     .build();
+//!
+//! let kalkulator = Kalkulator::new(kredyt)?;
+//!
+//! assert_eq!(kalkulator.kwota_kredytowania(), Kwota::from(560_000.0));
+//! assert!(kalkulator.koszt_kredytu(None) > kalkulator.koszt_kredytu(12));
+//! assert_eq!(kalkulator.mapa_kosztow()["prowizja"].total(), Kwota::from(11_200.0));
+//! # Ok::<(), hipoteka::Blad>(())
+//! ```
+
+mod blad;
+mod budowa;
+mod doplata;
+mod inflacja;
+mod inwestycja;
+mod kalkulator;
+mod koszt;
+mod koszty;
+mod kredyt;
+mod kredytobiorcy;
+mod kwota;
+mod maksymalny_kredyt;
+mod marza;
+mod mapa_rat;
+mod nadplaty;
+mod optymalizacja;
+mod premia;
+mod produkty;
+mod rata_banku;
+mod refinansowanie;
+mod siatka;
+mod splata;
+mod sprzedaz;
+mod stres;
+mod szukanie;
+mod waluta;
+mod wartosc;
+mod wynajem;
+mod symulacja;
+mod zdolnosc;
+
+pub use blad::{wczytaj_json, Blad};
+pub use budowa::{Budowa, Etap};
+pub use doplata::{Doplata, UtrataDoplaty, WarunekUtraty};
+pub use inflacja::{PorownanieRealne, WierszRealny, WynikPorownaniaRealnego};
+pub use inwestycja::{Inwestycja, NadplataCzyInwestycja, WierszInwestycji, WynikInwestycji, PODATEK_BELKI};
+pub use kalkulator::Kalkulator;
+pub use koszt::{Koszt, KosztBuilder, KosztKoncowy, Okres, Operator};
+pub use koszty::Koszty;
+pub use kredyt::{Kredyt, KredytBuilder, ProwizjaZaNadplate, Splata, ZmianaOprocentowania};
+pub use kredytobiorcy::{Kredytobiorca, RataKredytobiorcy, UdzialKredytobiorcy};
+pub use kwota::{Korekta, Kwota, Zaokraglenie, ZasadyZaokraglen};
+pub use maksymalny_kredyt::{MaksymalnyKredyt, WynikMaksymalnegoKredytu};
+pub use marza::{KosztWarunku, WarunekMarzy, ZmianaMarzy};
+pub use mapa_rat::{KalkulatorRaty, MapaRat, Rata};
+pub use nadplaty::{Nadplata, NadplataBuilder, Nadplaty};
+pub use optymalizacja::{Cel, Optymalizacja, WynikOptymalizacji};
+pub use premia::Premia;
+pub use produkty::{MinimalneWydatki, Produkt};
+pub use rata_banku::{OprocentowanieZRaty, TOLERANCJA_OPROCENTOWANIA};
+pub use refinansowanie::{Refinansowanie, RefinansowanieBuilder};
+pub use siatka::{Siatka, Wiersz, WynikSiatki, Zakres};
+pub use splata::StrukturaSplaty;
+pub use sprzedaz::{Sprzedaz, WynikSprzedazy, PODATEK_OD_SPRZEDAZY};
+pub use stres::{TestStresu, WierszStresu, WynikTestuStresu};
+pub use szukanie::{CelSplaty, SzukanaNadplata, Szukanie, WynikSzukania};
+pub use waluta::{KursyWaluty, TabelaKursow, Waluta};
+pub use wartosc::{Indeks, WartoscNieruchomosci, ZmianaWartosci};
+pub use wynajem::{PorownanieNajmu, RokPorownania, WynikPorownaniaNajmu};
+pub use symulacja::{ModelStopy, Percentyle, Symulacja, WynikSymulacji};
+pub use zdolnosc::{Gospodarstwo, Pozycja, ZdolnoscKredytowa};
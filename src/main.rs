@@ -1,32 +1,57 @@
-mod koszt;
-mod kredyt;
-mod koszty;
-mod kalkulator;
-mod mapa_rat;
-mod nadplaty;
+mod tryby;
 
-use kredyt::*;
-use koszty::*;
-use mapa_rat::*;
-use kalkulator::*;
+use hipoteka::Blad;
+use tryby::*;
 
-pub use koszt::*;
-pub use nadplaty::*;
+const UZYCIE: &str = "uzycie: hipoteka [tryb] <plik>
+
+tryby (domyslnie `oferta`):
+    oferta             data/kredyt/pekao_sa_25.json
+    refinansowanie     data/kredyt/pekao_sa_25_refinansowanie.json
+    siatka             data/siatka/pekao_sa_25.json
+    zdolnosc           data/zdolnosc/gospodarstwo.json
+    stres              data/stres/pekao_sa_25.json
+    symulacja          data/symulacja/pekao_sa_25.json
+    optymalizacja      data/optymalizacja/pekao_sa_25.json
+    szukanie           data/szukanie/mbank_10_lat.json
+    maksymalny_kredyt  data/maksymalny_kredyt/mbank.json
+    wynajem            data/wynajem/pekao_sa_25.json
+    inflacja           data/inflacja/pekao_sa.json
+    inwestycja         data/inwestycja/pekao_sa_25_lokata.json
+    sprzedaz           data/sprzedaz/pekao_sa_25_4_lata.json
+    koszty             data/koszty/pawia.json";
+
+fn uruchom(tryb: &str, path: &str) -> Result<(), Blad> {
+    match tryb {
+        "oferta" => oferta(path),
+        "refinansowanie" => refinansowanie(path),
+        "siatka" => siatka(path),
+        "zdolnosc" => zdolnosc(path),
+        "stres" => stres(path),
+        "symulacja" => symulacja(path),
+        "optymalizacja" => optymalizacja(path),
+        "szukanie" => szukanie(path),
+        "maksymalny_kredyt" => maksymalny_kredyt(path),
+        "wynajem" => wynajem(path),
+        "inflacja" => inflacja(path),
+        "inwestycja" => inwestycja(path),
+        "sprzedaz" => sprzedaz(path),
+        "koszty" => koszty(path),
+        _ => Err(Blad::Dane(format!("nieznany tryb `{}`\n\n{}", tryb, UZYCIE))),
+    }
+}
 
 fn main() {
-    // oferta("data/kredyt/mbank.json");
-    // oferta("data/kredyt/mbank_nadplata_new.json");
-    // oferta("data/kredyt/santander.json");
-    // oferta("data/kredyt/santander_25.json");
-    // oferta("data/kredyt/santander_nadplata_new.json");
-    // oferta("data/kredyt/millenium.json");
-    // oferta("data/kredyt/pekao_sa_ubezpiecznie.json");
-    // oferta("data/kredyt/pekao_sa.json");
-    // oferta("data/kredyt/pekao_sa_25.json");
-    oferta("data/kredyt/pekao_sa_25.json");
-    // oferta("data/kredyt/alior_bank.json");
-    // oferta("data/kredyt/alior_bank_nadplata_new.json");
-    // oferta("data/kredyt/alior_bank_25.json");
-    // koszty("data/koszty/pawia.json");
-    // koszty("data/koszty/uznanskiego.json");
-}
\ No newline at end of file
+    let argumenty: Vec<String> = std::env::args().skip(1).collect();
+    let wynik = match argumenty.as_slice() {
+        [] => uruchom("oferta", "data/kredyt/pekao_sa_25.json"),
+        [path] => uruchom("oferta", path),
+        [tryb, path] => uruchom(tryb, path),
+        _ => Err(Blad::Dane(UZYCIE.to_string())),
+    };
+
+    if let Err(blad) = wynik {
+        eprintln!("{}", blad);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, Kwota, Okres, Rata};
+
+/// Najwyzsza wartosc nieruchomosci, ktorej pierwsza rata miesci sie w `maksymalna_rata`.
+/// Oprocentowanie, okres i wklad wlasny sa brane z oferty, chyba ze zostana nadpisane.
+#[derive(Serialize, Deserialize)]
+pub struct MaksymalnyKredyt {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "maksymalna_rata")]
+    pub maksymalna_rata: Kwota,
+
+    #[serde(rename = "oprocentowanie", default)]
+    pub oprocentowanie: Option<f64>,
+
+    #[serde(rename = "okres_kredytowania", default)]
+    pub okres_kredytowania: Option<u64>,
+
+    #[serde(rename = "wklad_wlasny", default)]
+    pub wklad_wlasny: Option<f64>,
+}
+
+pub struct WynikMaksymalnegoKredytu {
+    pub kalkulator: Kalkulator,
+    pub rata: Kwota,
+    /// Wklad wlasny i koszty jednorazowe (PCC, prowizja, wycena), ktore trzeba pokryc gotowka.
+    pub gotowka: Kwota,
+}
+
+impl MaksymalnyKredyt {
+    pub fn oblicz(&self) -> Result<WynikMaksymalnegoKredytu, Blad> {
+        let mut kredyt = Kredyt::wczytaj(&self.oferta)?;
+        kredyt.oprocentowanie = self.oprocentowanie.unwrap_or(kredyt.oprocentowanie);
+        kredyt.okres_kredytowania = self.okres_kredytowania.unwrap_or(kredyt.okres_kredytowania);
+        kredyt.wklad_wlasny = self.wklad_wlasny.unwrap_or(kredyt.wklad_wlasny);
+
+        if !(0.0..100.0).contains(&kredyt.wklad_wlasny) {
+            return Err(Blad::Dane(format!("wklad wlasny oferty `{}` musi byc z przedzialu [0, 100)%, podano {}%", kredyt.nazwa, kredyt.wklad_wlasny)));
+        }
+
+        let pierwsza_rata = |zlotych: i64| {
+            let mut kredyt = kredyt.clone();
+            kredyt.wartosc_hipoteki = Kwota::z_groszy(zlotych * 100);
+            let kalkulator = Kalkulator::new(kredyt)?;
+            let rata = kalkulator.mapa_rat().values().next().map(|r| r.wartosc()).unwrap_or_default();
+
+            Ok((kalkulator, rata))
+        };
+
+        // oszacowanie z raty rownej, podwajane az rata przekroczy limit (np. przez promocje w ofercie)
+        let kwota_kredytowania = Rata::kwota_kredytu(self.maksymalna_rata, kredyt.oprocentowanie, kredyt.okres_kredytowania);
+        let mut dolna = 0;
+        let mut gorna = ((kwota_kredytowania.zl() / (1.0 - kredyt.wklad_wlasny / 100.0)) as i64).max(1);
+
+        while pierwsza_rata(gorna)?.1 <= self.maksymalna_rata {
+            dolna = gorna;
+            gorna *= 2;
+        }
+
+        // bisekcja po pelnych zlotych: rata przy `dolna` miesci sie w limicie, przy `gorna` juz nie
+        while gorna - dolna > 1 {
+            let srodek = (dolna + gorna) / 2;
+
+            if pierwsza_rata(srodek)?.1 <= self.maksymalna_rata {
+                dolna = srodek;
+            } else {
+                gorna = srodek;
+            }
+        }
+
+        let (kalkulator, rata) = pierwsza_rata(dolna)?;
+        let koszty_jednorazowe: Kwota = kalkulator.mapa_kosztow().values()
+            .filter(|k| k.okres() == Okres::Jednorazowy)
+            .map(|k| k.total())
+            .sum();
+        let gotowka = kalkulator.wartosc_hipoteki() - kalkulator.kwota_kredytowania() + koszty_jednorazowe;
+
+        Ok(WynikMaksymalnegoKredytu { kalkulator, rata, gotowka })
+    }
+}
+
+impl fmt::Display for WynikMaksymalnegoKredytu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let k = &self.kalkulator;
+
+        writeln!(f, "Maksymalny kredyt w banku `{}` ({:.2}%, {} rat):", k.nazwa(), k.oprocentowanie(), k.okres_kredytowania())?;
+        writeln!(f, "    * Wartosc nieruchomosci: {:.2}zl", k.wartosc_hipoteki())?;
+        writeln!(f, "    * Kwota kredytu: {:.2}zl, rata: {:.2}zl", k.kwota_kredytowania(), self.rata)?;
+        writeln!(f, "    * Wklad wlasny ({:.0}%): {:.2}zl", k.wklad_wlasny(), k.wartosc_hipoteki() - k.kwota_kredytowania())?;
+
+        for koszt in k.mapa_kosztow().values().filter(|k| k.okres() == Okres::Jednorazowy) {
+            writeln!(f, "    * {}", koszt)?;
+        }
+
+        writeln!(f, "Potrzebna gotowka: {:.2}zl", self.gotowka)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maksymalny_kredyt(wklad_wlasny: f64) -> Result<WynikMaksymalnegoKredytu, Blad> {
+        MaksymalnyKredyt {
+            oferta: "data/kredyt/pekao_sa_25.json".to_string(),
+            maksymalna_rata: Kwota::from(4_000.0),
+            oprocentowanie: None,
+            okres_kredytowania: None,
+            wklad_wlasny: Some(wklad_wlasny),
+        }.oblicz()
+    }
+
+    #[test]
+    fn najwyzsza_wartosc_z_rata_w_limicie() {
+        let wynik = maksymalny_kredyt(20.0).unwrap();
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        kredyt.wklad_wlasny = 20.0;
+        kredyt.wartosc_hipoteki = wynik.kalkulator.wartosc_hipoteki() + Kwota::from(1.0);
+        let rata_wyzej = Kalkulator::new(kredyt).unwrap().mapa_rat().values().next().unwrap().wartosc();
+
+        assert!(wynik.rata <= Kwota::from(4_000.0));
+        assert!(rata_wyzej > Kwota::from(4_000.0));
+    }
+
+    #[test]
+    fn pelny_wklad_wlasny_jest_bledem() {
+        assert!(maksymalny_kredyt(100.0).is_err());
+    }
+}
@@ -1,14 +1,14 @@
 use std::{collections::BTreeMap, fmt};
 
-use crate::{kredyt::Kredyt, Nadplaty};
+use crate::{kredyt::Kredyt, Korekta, Kwota, Nadplaty, Zaokraglenie, ZasadyZaokraglen};
 
 pub struct MapaRat {
     mapa: BTreeMap<u64, Rata>
 }
 
 impl MapaRat {
-    pub fn new(kwota_kredytowania: f64, dto: &Kredyt) -> Self {
-        let mapa = KalkulatorRaty::new(kwota_kredytowania, dto.oprocentowanie, dto.okres_kredytowania, dto.nadplaty.clone()).mapa_rat();
+    pub fn new(kwota_kredytowania: Kwota, dto: &Kredyt) -> Self {
+        let mapa = KalkulatorRaty::z_kredytu(kwota_kredytowania, dto).mapa_rat();
 
         Self { mapa }
     }
@@ -19,115 +19,323 @@ impl MapaRat {
 }
 
 pub struct KalkulatorRaty {
-    rata_poczatkowa: Rata,
-    kwota_kredytowania: f64,
+    kwota_kredytowania: Kwota,
     oprocentowanie: f64,
     okres_kredytowania: u64,
     nadplaty: Nadplaty,
+    zaokraglenie: ZasadyZaokraglen,
+    zmiany_oprocentowania: BTreeMap<u64, f64>,
+    od_raty: u64,
+    transze: BTreeMap<u64, Kwota>,
+    karencja: u64,
+    balon: Kwota,
 }
 
 impl KalkulatorRaty {
-    pub fn new(kwota_kredytowania: f64, oprocentowanie: f64, okres_kredytowania: u64, nadplaty: Nadplaty) -> Self {
-        let rata_poczatkowa = Rata::new(kwota_kredytowania, oprocentowanie, okres_kredytowania);
-
+    pub fn new(kwota_kredytowania: Kwota, oprocentowanie: f64, okres_kredytowania: u64, nadplaty: Nadplaty) -> Self {
         Self {
-            rata_poczatkowa,
             kwota_kredytowania,
             oprocentowanie,
             okres_kredytowania,
-            nadplaty
+            nadplaty,
+            zaokraglenie: ZasadyZaokraglen::default(),
+            zmiany_oprocentowania: BTreeMap::new(),
+            od_raty: 0,
+            transze: BTreeMap::new(),
+            karencja: 0,
+            balon: Kwota::ZERO,
+        }
+    }
+
+    /// Kalkulator z oprocentowaniem (wraz ze zmianami marzy), nadplatami, zasadami zaokraglen i etapami budowy oferty.
+    pub fn z_kredytu(kwota_kredytowania: Kwota, dto: &Kredyt) -> Self {
+        let kalkulator = dto.zmiany_oprocentowania_w_ratach().into_iter()
+            .fold(
+                Self::new(kwota_kredytowania, dto.oprocentowanie, dto.okres_kredytowania, dto.wszystkie_nadplaty()),
+                |kalkulator, od_raty| kalkulator.zmiana_oprocentowania(od_raty, dto.oprocentowanie_w_racie(od_raty))
+            )
+            .zaokraglenie(dto.zaokraglenie)
+            .karencja(dto.struktura_splaty.karencja(dto.okres_kredytowania))
+            .balon(dto.struktura_splaty.balon(kwota_kredytowania));
+
+        match &dto.budowa {
+            Some(budowa) => {
+                let karencja = kalkulator.karencja.max(budowa.karencja());
+                kalkulator.transze(budowa.transze(kwota_kredytowania)).karencja(karencja)
+            },
+            None => kalkulator,
         }
     }
 
+    pub fn nadplaty(mut self, nadplaty: Nadplaty) -> Self {
+        self.nadplaty = nadplaty;
+        self
+    }
+
+    pub fn zaokraglenie(mut self, zaokraglenie: ZasadyZaokraglen) -> Self {
+        self.zaokraglenie = zaokraglenie;
+        self
+    }
+
+    /// Harmonogram zaczyna sie od raty `od_raty` kredytu (np. po refinansowaniu), numery rat,
+    /// nadplat i zmian oprocentowania sa liczone od poczatku kredytu.
+    pub fn od_raty(mut self, od_raty: u64) -> Self {
+        self.od_raty = od_raty;
+        self
+    }
+
+    /// Kredyt wyplacany w transzach - kwoty wyplacane na poczatku kolejnych miesiecy, sumujace sie do kwoty kredytu.
+    pub fn transze(mut self, transze: BTreeMap<u64, Kwota>) -> Self {
+        self.transze = transze;
+        self
+    }
+
+    /// Do raty `karencja` splacane sa tylko odsetki, potem rata jest liczona na pozostaly okres.
+    pub fn karencja(mut self, karencja: u64) -> Self {
+        self.karencja = karencja;
+        self
+    }
+
+    /// Czesc kapitalu splacana w ostatniej racie, raty rowne sa liczone tak, aby do niej pozostala.
+    pub fn balon(mut self, balon: Kwota) -> Self {
+        self.balon = balon;
+        self
+    }
+
+    /// Od raty `od_raty` obowiazuje nowe oprocentowanie, rata jest wtedy przeliczana.
+    pub fn zmiana_oprocentowania(mut self, od_raty: u64, oprocentowanie: f64) -> Self {
+        self.zmiany_oprocentowania.insert(od_raty, oprocentowanie);
+        self
+    }
+
+    pub fn oprocentowanie(&self, numer_raty: u64) -> f64 {
+        self.zmiany_oprocentowania.range(..=numer_raty)
+            .next_back()
+            .map(|(_, oprocentowanie)| *oprocentowanie)
+            .unwrap_or(self.oprocentowanie)
+    }
+
+    /// Harmonogram rat rownych. Nadplata zmniejsza kolejne raty, okres kredytowania sie nie zmienia.
     pub fn mapa_rat(&self) -> BTreeMap<u64, Rata> {
-        let oprocentowanie = self.oprocentowanie / 100.0;
-        let mut kapital_do_splaty = self.kwota_kredytowania;
-        let mut aktualna_rata = self.rata_poczatkowa;
-        let mut ulga_od_nadplaty = 0.0;
-
-        let mut retval: BTreeMap<u64, Rata> = (0..self.okres_kredytowania).map(|numer_raty| {
-            let odsetki = kapital_do_splaty * oprocentowanie * 30.4375 / 365.0;
-            let kapital = (aktualna_rata.wartosc() - odsetki).clamp(0.0, kapital_do_splaty);
-            // let nadplata = self.nadplaty.wartosc(numer_raty);
-            // let pozostaly_okres_kredytowania = self.okres_kredytowania - numer_raty;
-            // musisz podzielic kapital na wszystkie miesiace
-            let rata = Rata { kapital, odsetki, nadplata: 0.0 };
-
-            aktualna_rata = rata;
-            kapital_do_splaty = (kapital_do_splaty - aktualna_rata.kapital).clamp(0.0, self.kwota_kredytowania);
-            // kapital_do_splaty = (kapital_do_splaty - aktualna_rata.kapital - nadplata).clamp(0.0, self.kwota_kredytowania);
-            // ulga_od_nadplaty = nadplata / pozostaly_okres_kredytowania as f64;
-            // aktualna_rata.ulga(ulga_od_nadplaty);
-
-            (numer_raty, rata)
-        })
-        .collect();
-
-        dbg!(retval.values().map(|rata| rata.kapital()).sum::<f64>());
-
-        for numer_raty in 0..self.okres_kredytowania {
-                let nadplata = self.nadplaty.wartosc(numer_raty);
-                let pozostaly_okres_kredytowania = self.okres_kredytowania - numer_raty - 1;
-                let parts: u64 = (1..=pozostaly_okres_kredytowania).sum();
-                let ulga_od_nadplaty = nadplata / parts as f64;
-                let kapital_splacony = retval.values()
-                    .map(|rata| rata.kapital())
-                    .take(numer_raty as usize + 1)
-                    .sum::<f64>();
-                let kapital_do_splaty = (self.kwota_kredytowania - kapital_splacony).max(0.0);
-
-                retval.get_mut(&numer_raty).map(|rata| {
-                    rata.nadplata(nadplata);
-                    rata.odsetki = kapital_do_splaty * oprocentowanie * 30.4375 / 365.0;
-                });
-
-            for (i, (_, rata)) in retval.iter_mut().skip(numer_raty as usize + 1).enumerate() {
-                rata.ulga(ulga_od_nadplaty * (i + 1) as f64);
+        let metoda = self.zaokraglenie.metoda;
+        let mut kapital_do_splaty = self.kwota_kredytowania - self.transze.values().copied().sum::<Kwota>();
+        let mut rata = Rata::new(self.bez_balonu(kapital_do_splaty, self.oprocentowanie, self.okres_kredytowania), self.oprocentowanie, self.okres_kredytowania, metoda).wartosc();
+        let mut poprzednie_oprocentowanie = self.oprocentowanie;
+        let mut przelicz_rate = false;
+        let mut retval = BTreeMap::new();
+
+        for numer_raty in self.od_raty..self.od_raty + self.okres_kredytowania {
+            if let Some(&transza) = self.transze.get(&numer_raty) {
+                kapital_do_splaty += transza;
+                przelicz_rate = true;
+            }
+
+            if kapital_do_splaty <= Kwota::ZERO && self.transze.range(numer_raty..).next().is_none() {
+                break;
+            }
+
+            let pozostalo_rat = self.od_raty + self.okres_kredytowania - numer_raty;
+            let oprocentowanie = self.oprocentowanie(numer_raty);
+
+            if przelicz_rate || oprocentowanie != poprzednie_oprocentowanie || self.zaokraglenie.korekta == Korekta::CoMiesiac {
+                rata = Rata::new(self.bez_balonu(kapital_do_splaty, oprocentowanie, pozostalo_rat), oprocentowanie, pozostalo_rat, metoda).wartosc();
             }
+
+            let odsetki = Rata::odsetki(kapital_do_splaty, oprocentowanie, metoda);
+            let kapital = if pozostalo_rat == 1 {
+                kapital_do_splaty
+            } else if numer_raty < self.karencja {
+                Kwota::ZERO
+            } else {
+                (rata - odsetki).clamp(Kwota::ZERO, kapital_do_splaty)
+            };
+            let nadplata = self.nadplaty.wartosc(numer_raty).min(kapital_do_splaty - kapital);
+
+            kapital_do_splaty -= kapital + nadplata;
+            przelicz_rate = nadplata > Kwota::ZERO || numer_raty < self.karencja;
+            poprzednie_oprocentowanie = oprocentowanie;
+
+            retval.insert(numer_raty, Rata { kapital, odsetki, nadplata, ..Rata::default() });
         }
 
         retval
     }
+
+    /// Kapital splacany ratami rownymi - pomniejszony o wartosc biezaca raty balonowej.
+    fn bez_balonu(&self, kapital_do_splaty: Kwota, oprocentowanie: f64, pozostalo_rat: u64) -> Kwota {
+        let balon = self.balon.min(kapital_do_splaty);
+
+        kapital_do_splaty - balon * (1.0 + oprocentowanie / 100.0 / 12.0).powf(-(pozostalo_rat as f64))
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Rata {
-    pub kapital: f64,
-    pub odsetki: f64,
-    pub nadplata: f64,
+    pub kapital: Kwota,
+    pub odsetki: Kwota,
+    pub nadplata: Kwota,
+    /// Roznica miedzy splacanym w racie kapitalem po biezacym kursie i po kursie z dnia uruchomienia kredytu walutowego.
+    pub roznice_kursowe: Kwota,
+    /// Roznice kursowe od nadplaty - placone razem z nadplata, poza rata.
+    pub roznice_kursowe_nadplaty: Kwota,
+    /// Czesc raty splacana przez program doplat, ujemna przy zwrocie doplat.
+    pub doplata: Kwota,
 }
 
 impl Rata {
-    pub fn wartosc(&self) -> f64 { self.kapital + self.odsetki }
+    /// Kwota placona przez kredytobiorce, bez nadplaty.
+    pub fn wartosc(&self) -> Kwota { self.kapital + self.odsetki + self.roznice_kursowe - self.doplata }
 
-    pub fn new(kwota_kredytowania: f64, oprocentowanie: f64, okres_kredytowania: u64) -> Self {
+    pub fn new(kwota_kredytowania: Kwota, oprocentowanie: f64, okres_kredytowania: u64, zaokraglenie: Zaokraglenie) -> Self {
         let n = 12.0; // liczba rat w ciągu roku
         let okres_kredytowania = okres_kredytowania as f64;
         let oprocentowanie = oprocentowanie / 100.0;
-        let rata = kwota_kredytowania * oprocentowanie / (n * (1.0 - (n / (n + oprocentowanie)).powf(okres_kredytowania)));
-        let odsetki = kwota_kredytowania * oprocentowanie * 30.4375 / 365.25;
-        let kapital = rata - odsetki;
+        let rata = if oprocentowanie == 0.0 {
+            kwota_kredytowania.razy(1.0 / okres_kredytowania, zaokraglenie)
+        } else {
+            kwota_kredytowania.razy(oprocentowanie / (n * (1.0 - (n / (n + oprocentowanie)).powf(okres_kredytowania))), zaokraglenie)
+        };
+        let odsetki = Self::odsetki(kwota_kredytowania, oprocentowanie * 100.0, zaokraglenie);
 
         Self {
-            kapital,
+            kapital: rata - odsetki,
             odsetki,
-            nadplata: 0.0
+            nadplata: Kwota::ZERO,
+            ..Self::default()
         }
     }
 
-    pub fn ulga(&mut self, nadplata: f64) {
-        self.kapital = (self.kapital - nadplata).max(0.0);
+    /// Kwota kredytu, ktora mozna splacic rata rowna `rata` - odwrotnosc `Rata::new`.
+    pub fn kwota_kredytu(rata: Kwota, oprocentowanie: f64, okres_kredytowania: u64) -> Kwota {
+        let oprocentowanie = oprocentowanie / 100.0 / 12.0;
+
+        if oprocentowanie == 0.0 {
+            rata * okres_kredytowania
+        } else {
+            rata.razy((1.0 - (1.0 + oprocentowanie).powf(-(okres_kredytowania as f64))) / oprocentowanie, Zaokraglenie::WDol)
+        }
     }
 
-    pub fn nadplata(&mut self, nadplata: f64) {
-        self.nadplata = nadplata
+    /// Oprocentowanie, przy ktorym `Rata::new` daje rate `rata` - odwrotnosc `Rata::new` szukana
+    /// bisekcja, bo rata rosnie wraz z oprocentowaniem. `None`, gdy rata nie odpowiada zadnemu
+    /// oprocentowaniu z przedzialu 0% - 100%.
+    pub fn oprocentowanie(kwota_kredytowania: Kwota, rata: Kwota, okres_kredytowania: u64, zaokraglenie: Zaokraglenie) -> Option<f64> {
+        let (mut dolne, mut gorne) = (0.0, 100.0);
+        let rata_przy = |oprocentowanie: f64| Self::new(kwota_kredytowania, oprocentowanie, okres_kredytowania, zaokraglenie).wartosc();
+
+        if rata < rata_przy(dolne) || rata > rata_przy(gorne) {
+            return None;
+        }
+
+        for _ in 0..100 {
+            let srodek = (dolne + gorne) / 2.0;
+
+            if rata_przy(srodek) < rata {
+                dolne = srodek;
+            } else {
+                gorne = srodek;
+            }
+        }
+
+        Some((dolne + gorne) / 2.0)
+    }
+
+    /// Odsetki za jeden miesiac (30.4375 / 365.25 roku) od podanego kapitalu.
+    pub fn odsetki(kapital: Kwota, oprocentowanie: f64, zaokraglenie: Zaokraglenie) -> Kwota {
+        kapital.razy(oprocentowanie / 100.0 * 30.4375 / 365.25, zaokraglenie)
     }
 
-    pub fn kapital(&self) -> f64 { self.kapital + self.nadplata }
+    pub fn kapital(&self) -> Kwota { self.kapital + self.nadplata }
+
+    /// Wszystko, co kredytobiorca wplaca w danym miesiacu: rata i nadplata z jej roznicami kursowymi.
+    pub fn wplata(&self) -> Kwota { self.wartosc() + self.nadplata + self.roznice_kursowe_nadplaty }
+
+    /// Czesc raty i nadplaty bedaca kosztem kredytu dla kredytobiorcy.
+    pub fn koszt(&self) -> Kwota { self.odsetki + self.roznice_kursowe + self.roznice_kursowe_nadplaty - self.doplata }
 }
 
 impl fmt::Display for Rata {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:.2} = kapital: {:.2}, odsetki: {:.2}, nadplata: {:.2}", self.wartosc() + self.nadplata, self.kapital, self.odsetki, self.nadplata)
+        write!(f, "{:.2} = kapital: {:.2}, odsetki: {:.2}, nadplata: {:.2}", self.wplata(), self.kapital, self.odsetki, self.nadplata)?;
+
+        if self.roznice_kursowe != Kwota::ZERO || self.roznice_kursowe_nadplaty != Kwota::ZERO {
+            write!(f, ", roznice kursowe: {:.2}", self.roznice_kursowe + self.roznice_kursowe_nadplaty)?;
+        }
+
+        if self.doplata != Kwota::ZERO {
+            write!(f, ", doplata: {:.2}", self.doplata)?;
+        }
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Nadplata;
+
+    #[test]
+    fn odsetki_miesieczne_to_dwunasta_czesc_rocznych() {
+        let odsetki = Rata::odsetki(Kwota::from(300_000.0), 7.5, Zaokraglenie::default());
+
+        assert_eq!(odsetki, Kwota::from(1875.0));
+    }
+
+    #[test]
+    fn nadplata_obniza_kolejne_raty_bez_zmiany_okresu() {
+        let kwota = Kwota::from(300_000.0);
+        let nadplaty = Nadplaty::new(vec![Nadplata { wartosc: Kwota::from(50_000.0), from: 12, to: 13, po_okresie: false }]);
+        let mapa = KalkulatorRaty::new(kwota, 7.5, 300, nadplaty).mapa_rat();
+        let bez_nadplat = KalkulatorRaty::new(kwota, 7.5, 300, Nadplaty::default()).mapa_rat();
+
+        assert_eq!(mapa[&0].odsetki, Rata::new(kwota, 7.5, 300, Zaokraglenie::default()).odsetki);
+        assert_eq!(mapa.len(), 300);
+        assert!(mapa[&13].wartosc() < bez_nadplat[&13].wartosc());
+        assert_eq!(mapa.values().map(|r| r.kapital()).sum::<Kwota>(), kwota);
+    }
+
+    /// Harmonogram 100 000 zl na 6% w 12 ratach rownych: odsetki od salda na poczatek miesiaca
+    /// zaokraglane do grosza, tak jak w harmonogramach bankow (rata 8 606,64 zl).
+    fn harmonogram(metoda: Zaokraglenie, korekta: Korekta) -> Vec<Kwota> {
+        KalkulatorRaty::new(Kwota::from(100_000.0), 6.0, 12, Nadplaty::default())
+            .zaokraglenie(ZasadyZaokraglen { metoda, korekta })
+            .mapa_rat()
+            .values()
+            .map(|r| r.wartosc())
+            .collect()
+    }
+
+    fn raty(raty: &[f64]) -> Vec<Kwota> {
+        raty.iter().map(|&r| Kwota::from(r)).collect()
+    }
+
+    #[test]
+    fn harmonogram_zgodny_z_bankowym_co_do_grosza() {
+        let mut oczekiwane = vec![8606.64; 11];
+        oczekiwane.push(8606.69);
+
+        assert_eq!(harmonogram(Zaokraglenie::Matematyczne, Korekta::OstatniaRata), raty(&oczekiwane));
+        assert_eq!(harmonogram(Zaokraglenie::Bankowe, Korekta::OstatniaRata), raty(&oczekiwane));
+
+        let mut w_gore = vec![8606.65; 11];
+        w_gore.push(8606.62);
+        assert_eq!(harmonogram(Zaokraglenie::WGore, Korekta::OstatniaRata), raty(&w_gore));
+
+        let mut w_dol = vec![8606.64; 11];
+        w_dol.push(8606.62);
+        assert_eq!(harmonogram(Zaokraglenie::WDol, Korekta::OstatniaRata), raty(&w_dol));
+    }
+
+    #[test]
+    fn korekta_co_miesiac_przelicza_rate_z_pozostalego_kapitalu() {
+        assert_eq!(
+            harmonogram(Zaokraglenie::Matematyczne, Korekta::CoMiesiac),
+            raty(&[8606.64, 8606.64, 8606.64, 8606.64, 8606.64, 8606.65, 8606.65, 8606.64, 8606.65, 8606.64, 8606.65, 8606.65])
+        );
+        assert_eq!(
+            harmonogram(Zaokraglenie::WDol, Korekta::CoMiesiac),
+            raty(&[8606.64, 8606.64, 8606.64, 8606.64, 8606.64, 8606.64, 8606.64, 8606.64, 8606.64, 8606.64, 8606.63, 8606.63])
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, Kwota};
+
+/// Zmiana marzy o `zmiana` punktow procentowych w ratach `od..do`, np. promocyjna marza
+/// przez pierwszy rok: `{"od": 0, "do": 12, "zmiana": -1.0}`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ZmianaMarzy {
+    #[serde(rename = "od", default)]
+    pub od: u64,
+
+    #[serde(rename = "do", default)]
+    pub to: Option<u64>,
+
+    #[serde(rename = "zmiana")]
+    pub zmiana: f64,
+}
+
+/// Warunek sprzedazy wiazanej (konto z wplywami, karta, ubezpieczenie). Gdy nie jest spelniony,
+/// marza rosnie o `podwyzka_marzy` punktow procentowych.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WarunekMarzy {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    #[serde(rename = "podwyzka_marzy")]
+    pub podwyzka_marzy: f64,
+
+    /// Pierwsza rata, w ktorej warunek jest spelniony.
+    #[serde(rename = "spelniony_od", default)]
+    pub spelniony_od: u64,
+
+    /// Rata, od ktorej warunek przestaje byc spelniony, np. po zamknieciu konta.
+    #[serde(rename = "spelniony_do", default)]
+    pub spelniony_do: Option<u64>,
+}
+
+impl ZmianaMarzy {
+    pub fn obowiazuje(&self, numer_raty: u64) -> bool {
+        self.od <= numer_raty && self.to.map_or(true, |to| numer_raty < to)
+    }
+}
+
+impl WarunekMarzy {
+    pub fn spelniony(&self, numer_raty: u64) -> bool {
+        self.spelniony_od <= numer_raty && self.spelniony_do.map_or(true, |to| numer_raty < to)
+    }
+}
+
+pub struct KosztWarunku {
+    pub nazwa: String,
+    pub podwyzka_marzy: f64,
+    /// Dodatkowy koszt kredytu, gdy warunek nie jest spelniony od pierwszej raty.
+    pub od_poczatku: Kwota,
+    /// Dodatkowy koszt kredytu przy zerwaniu warunku od raty `spelniony_do` z oferty.
+    pub wg_oferty: Option<(u64, Kwota)>,
+}
+
+impl KosztWarunku {
+    /// Koszt zerwania kazdego warunku (rezygnacji z kazdego produktu) wzgledem jego dotrzymania
+    /// przez caly okres, przy pozostalych warunkach jak w ofercie. Rezygnacja z produktu konczy
+    /// tez jego oplate, wiec ujemny koszt oznacza, ze produkt sie nie oplaca.
+    pub fn oblicz(kredyt: &Kredyt) -> Result<Vec<KosztWarunku>, Blad> {
+        let koszt = |zmien: &dyn Fn(&mut Kredyt)| {
+            let mut kredyt = kredyt.clone();
+            zmien(&mut kredyt);
+
+            Ok(Kalkulator::new(kredyt)?.koszt_kredytu(None))
+        };
+        let koszt_warunku = |nazwa: &str, podwyzka_marzy: f64, zerwany: Option<u64>, zerwij: &dyn Fn(&mut Kredyt, Option<u64>)| {
+            let dotrzymany = koszt(&|k: &mut Kredyt| zerwij(k, None))?;
+            let wg_oferty = match zerwany {
+                Some(od) => Some((od, koszt(&|k: &mut Kredyt| zerwij(k, Some(od)))? - dotrzymany)),
+                None => None,
+            };
+
+            Ok(KosztWarunku {
+                nazwa: nazwa.to_string(),
+                podwyzka_marzy,
+                od_poczatku: koszt(&|k: &mut Kredyt| zerwij(k, Some(0)))? - dotrzymany,
+                wg_oferty,
+            })
+        };
+
+        let warunki = kredyt.warunki_marzy.iter().enumerate().map(|(i, w)| {
+            koszt_warunku(&w.nazwa, w.podwyzka_marzy, w.spelniony_do, &|k: &mut Kredyt, od| k.warunki_marzy[i].spelniony_do = od)
+        });
+        let produkty = kredyt.produkty.iter().enumerate().map(|(i, p)| {
+            koszt_warunku(&p.nazwa, p.obnizka_marzy, p.rezygnacja, &|k: &mut Kredyt, od| k.produkty[i].rezygnacja = od)
+        });
+
+        warunki.chain(produkty).collect()
+    }
+}
+
+impl fmt::Display for KosztWarunku {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let opis = |koszt: Kwota| if koszt < Kwota::ZERO { ("oszczedza", -koszt) } else { ("kosztuje", koszt) };
+        let (czasownik, koszt) = opis(self.od_poczatku);
+
+        write!(f, "    * {} (+{:.2}pp): niespelnienie od poczatku {} {:.2}zl", self.nazwa, self.podwyzka_marzy, czasownik, koszt)?;
+
+        if let Some((od, koszt)) = self.wg_oferty {
+            let (czasownik, koszt) = opis(koszt);
+            write!(f, ", zerwanie od {}. raty {} {:.2}zl", od + 1, czasownik, koszt)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oprocentowanie_z_promocja_i_zerwanym_warunkiem() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_promocja.json").unwrap();
+
+        assert!((kredyt.oprocentowanie_w_racie(11) - 5.94).abs() < 1e-9);
+        assert!((kredyt.oprocentowanie_w_racie(12) - 6.44).abs() < 1e-9);
+        assert!((kredyt.oprocentowanie_w_racie(60) - 6.74).abs() < 1e-9);
+        assert_eq!(kredyt.zmiany_oprocentowania_w_ratach(), vec![0, 12, 60]);
+    }
+
+    #[test]
+    fn zerwanie_warunku_zwieksza_koszt_kredytu() {
+        let kalkulator = Kalkulator::new(Kredyt::wczytaj("data/kredyt/pekao_sa_25_promocja.json").unwrap()).unwrap();
+        let warunki = kalkulator.koszty_warunkow().unwrap();
+        let konto = &warunki[0];
+
+        assert_eq!(warunki.len(), 2);
+        assert_eq!(konto.nazwa, "konto z wplywami");
+        assert!(konto.od_poczatku > konto.wg_oferty.unwrap().1);
+        assert!(konto.wg_oferty.unwrap().1 > Kwota::ZERO);
+        assert!(warunki[1].wg_oferty.is_none());
+    }
+}
@@ -1,11 +1,26 @@
-use std::{fmt, cmp};
+use std::fmt;
 use serde::{Serialize, Deserialize};
+use typed_builder::TypedBuilder;
+
+use crate::Kwota;
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Nadplaty(Vec<Nadplata>);
 
 impl Nadplaty {
-    pub fn wartosc(&self, numer_raty: u64) -> f64 {
+    pub fn new(nadplaty: Vec<Nadplata>) -> Self {
+        Self(nadplaty)
+    }
+
+    pub fn dodaj(&mut self, nadplata: Nadplata) {
+        self.0.push(nadplata);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Nadplata> {
+        self.0.iter()
+    }
+
+    pub fn wartosc(&self, numer_raty: u64) -> Kwota {
         self.0
             .iter()
             .filter(|n| {
@@ -40,20 +55,23 @@ impl fmt::Display for Nadplaty {
     }
 }
 
-#[derive(Clone, Default, Serialize, Deserialize)]
+/// Nadplata w miesiacach `from..to`, albo - gdy `po_okresie` - jednorazowo w miesiacu `to`
+/// w wysokosci sumy miesiecznych wplat z calego okresu.
+#[derive(Clone, Default, Serialize, Deserialize, TypedBuilder)]
 pub struct Nadplata {
-    pub wartosc: f64,
+    pub wartosc: Kwota,
     #[serde(rename = "od")]
     pub from: u64,
     #[serde(rename = "do")]
     pub to: u64,
+    #[builder(default)]
     pub po_okresie: bool
 }
 
 impl Nadplata {
-    pub fn wartosc(&self) -> f64 {
+    pub fn wartosc(&self) -> Kwota {
         if self.po_okresie {
-            cmp::max(0, self.to - self.from) as f64 * self.wartosc
+            self.wartosc * self.to.saturating_sub(self.from)
         } else {
             self.wartosc
         }
@@ -0,0 +1,240 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, Kwota, Nadplata, Nadplaty};
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cel {
+    /// Najnizszy calkowity koszt kredytu.
+    Koszt,
+    /// Najszybsza splata kredytu.
+    Czas,
+}
+
+/// Zachlanne przeszukiwanie harmonogramu nadplat, przy ktorym rata i nadplata mieszcza sie
+/// w miesiecznym budzecie. W kazdym segmencie nadwyzka budzetu jest albo nadplacana co miesiac,
+/// albo odkladana i nadplacana jednorazowo pozniej - wybierany jest wariant o nizszym koszcie
+/// calego kredytu (albo krotszym okresie). Segmenty koncza sie tez na koncu okresu prowizji
+/// za nadplate, w racie, w ktorej LTV spada ponizej progu kosztu z `do_ltv`, i w ratach, w ktorych
+/// rata moze wzrosnac (zmiana oprocentowania albo marzy, koniec karencji, transza).
+#[derive(Serialize, Deserialize)]
+pub struct Optymalizacja {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "budzet")]
+    pub budzet: Kwota,
+
+    #[serde(rename = "cel", default = "Optymalizacja::domyslny_cel")]
+    pub cel: Cel,
+
+    /// Najdluzszy segment, w ktorym kwota nadplaty jest stala, w miesiacach.
+    #[serde(rename = "segment", default = "Optymalizacja::domyslny_segment")]
+    pub segment: u64,
+}
+
+pub struct WynikOptymalizacji {
+    pub nazwa: String,
+    pub nadplaty: Nadplaty,
+    pub koszt_kredytu: Kwota,
+    pub liczba_rat: u64,
+    pub koszt_bez_nadplat: Kwota,
+    pub liczba_rat_bez_nadplat: u64,
+}
+
+impl Optymalizacja {
+    fn domyslny_cel() -> Cel { Cel::Koszt }
+
+    fn domyslny_segment() -> u64 { 12 }
+
+    pub fn oblicz(&self) -> Result<WynikOptymalizacji, Blad> {
+        self.optymalizuj(Kredyt::wczytaj(&self.oferta)?)
+    }
+
+    fn optymalizuj(&self, mut kredyt: Kredyt) -> Result<WynikOptymalizacji, Blad> {
+        kredyt.nadplaty = Nadplaty::default();
+
+        let bez_nadplat = Kalkulator::new(kredyt.clone())?;
+        let mut plan: Vec<Nadplata> = Vec::new();
+        let mut odlozone = Kwota::ZERO;
+        let mut od = 0;
+
+        while od < kredyt.okres_kredytowania {
+            let kalkulator = self.kalkulator(&kredyt, plan.clone())?;
+            if !kalkulator.mapa_rat().contains_key(&od) {
+                break;
+            }
+
+            // nadplaty obnizaja kolejne raty segmentu, wiec najwyzsza rata bez nich ogranicza nadwyzke
+            let punkty = self.punkty_zmian(&kredyt, &kalkulator, od);
+            let to = punkty[0];
+            let najwyzsza_rata = kalkulator.mapa_rat().range(od..to).map(|(_, r)| r.wartosc()).max().unwrap_or_default();
+            let nadwyzka = (self.budzet - najwyzsza_rata).max(Kwota::ZERO);
+            let ocena = |mut nadplaty: Vec<Nadplata>, od_raty: u64| {
+                nadplaty.push(Nadplata { wartosc: nadwyzka, from: od_raty, to: kredyt.okres_kredytowania, po_okresie: false });
+
+                Ok(self.ocena(&self.kalkulator(&kredyt, nadplaty)?))
+            };
+
+            let mut teraz = plan.clone();
+            teraz.push(Self::jednorazowa(odlozone, od));
+
+            // odkladanie nadwyzki az do kolejnego punktu zmiany, a potem nadplaty co miesiac
+            let najlepsze_odlozenie = punkty.iter()
+                .map(|&punkt| {
+                    let mut pozniej = plan.clone();
+                    pozniej.push(Self::jednorazowa(odlozone + nadwyzka * (punkt - od), punkt));
+
+                    ocena(pozniej, punkt)
+                })
+                .collect::<Result<Vec<_>, Blad>>()?
+                .into_iter()
+                .min();
+
+            let nadplata_teraz = ocena(teraz.clone(), od)?;
+
+            if najlepsze_odlozenie.iter().all(|&najlepsza| nadplata_teraz <= najlepsza) {
+                teraz.push(Nadplata { wartosc: nadwyzka, from: od, to, po_okresie: false });
+                plan = teraz;
+                odlozone = Kwota::ZERO;
+            } else {
+                odlozone += nadwyzka * (to - od);
+            }
+
+            od = to;
+        }
+
+        if odlozone > Kwota::ZERO {
+            plan.push(Self::jednorazowa(odlozone, od));
+        }
+
+        plan.retain(|n| n.wartosc > Kwota::ZERO);
+        let nadplaty = Nadplaty::new(plan);
+        let kalkulator = self.kalkulator(&kredyt, nadplaty.iter().cloned().collect())?;
+
+        Ok(WynikOptymalizacji {
+            nazwa: kredyt.nazwa,
+            nadplaty,
+            koszt_kredytu: kalkulator.koszt_kredytu(None),
+            liczba_rat: kalkulator.mapa_rat().len() as u64,
+            koszt_bez_nadplat: bez_nadplat.koszt_kredytu(None),
+            liczba_rat_bez_nadplat: bez_nadplat.mapa_rat().len() as u64,
+        })
+    }
+
+    fn kalkulator(&self, kredyt: &Kredyt, nadplaty: Vec<Nadplata>) -> Result<Kalkulator, Blad> {
+        let mut kredyt = kredyt.clone();
+        kredyt.nadplaty = Nadplaty::new(nadplaty);
+
+        Kalkulator::new(kredyt)
+    }
+
+    fn ocena(&self, kalkulator: &Kalkulator) -> (i64, i64) {
+        let koszt = kalkulator.koszt_kredytu(None).grosze();
+        let liczba_rat = kalkulator.mapa_rat().len() as i64;
+
+        match self.cel {
+            Cel::Koszt => (koszt, liczba_rat),
+            Cel::Czas => (liczba_rat, koszt),
+        }
+    }
+
+    fn jednorazowa(wartosc: Kwota, numer_raty: u64) -> Nadplata {
+        Nadplata { wartosc, from: numer_raty, to: numer_raty + 1, po_okresie: false }
+    }
+
+    /// Raty po `od`, w ktorych oplaca sie rozwazyc nadplate odlozonej nadwyzki: koniec segmentu,
+    /// koniec okresu prowizji za nadplate, raty, w ktorych LTV spada ponizej progu ktoregos z kosztow,
+    /// oraz raty, w ktorych rata moze wzrosnac - zmiany oprocentowania i marzy, koniec karencji
+    /// i wyplaty transz. Pierwsza z nich konczy biezacy segment.
+    fn punkty_zmian(&self, kredyt: &Kredyt, kalkulator: &Kalkulator, od: u64) -> Vec<u64> {
+        let wartosc = kredyt.wartosc_hipoteki.zl();
+        let ponizej = |n: u64, ltv: f64| kalkulator.kapital_do_splaty(n).zl() * 100.0 < wartosc * ltv;
+        let progi_ltv = kredyt.koszty.iter().filter_map(|k| k.do_ltv()).filter(|&ltv| !ponizej(od, ltv)).filter_map(|ltv| {
+            (od + 1..kredyt.okres_kredytowania).find(|&n| ponizej(n, ltv))
+        });
+        let budowa = kredyt.budowa.iter().flat_map(|b| b.etapy.iter().map(|e| e.miesiac).chain(std::iter::once(b.karencja())));
+        let mut punkty: Vec<u64> = kredyt.prowizja_za_nadplate.iter().map(|p| p.do_raty)
+            .chain(progi_ltv)
+            .chain(kredyt.zmiany_oprocentowania_w_ratach())
+            .chain(std::iter::once(kredyt.struktura_splaty.karencja(kredyt.okres_kredytowania)))
+            .chain(budowa)
+            .chain(std::iter::once((od + self.segment.max(1)).min(kredyt.okres_kredytowania)))
+            .filter(|&n| n > od && n <= kredyt.okres_kredytowania)
+            .collect();
+        punkty.sort_unstable();
+        punkty.dedup();
+
+        punkty
+    }
+}
+
+impl fmt::Display for WynikOptymalizacji {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Optymalizacja nadplat oferty `{}`:", self.nazwa)?;
+        writeln!(f, "    * Bez nadplat: {} rat, koszt kredytu {:.2}zl", self.liczba_rat_bez_nadplat, self.koszt_bez_nadplat)?;
+        writeln!(f, "    * Z nadplatami: {} rat, koszt kredytu {:.2}zl", self.liczba_rat, self.koszt_kredytu)?;
+        writeln!(f, "\"nadplaty\": {}", serde_json::to_string_pretty(&self.nadplaty).map_err(|_| fmt::Error)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ProwizjaZaNadplate, ZmianaOprocentowania};
+
+    fn optymalizacja(procent: f64) -> WynikOptymalizacji {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_prowizja.json").unwrap();
+        kredyt.prowizja_za_nadplate = Some(ProwizjaZaNadplate { procent, do_raty: 30 });
+        let optymalizacja = Optymalizacja {
+            oferta: String::new(),
+            budzet: Kwota::from(6_000.0),
+            cel: Cel::Koszt,
+            segment: 12,
+        };
+
+        optymalizacja.optymalizuj(kredyt).unwrap()
+    }
+
+    #[test]
+    fn wysoka_prowizja_odklada_nadplaty_do_konca_okresu_prowizji() {
+        let wynik = optymalizacja(20.0);
+        let pierwsza = wynik.nadplaty.iter().map(|n| n.from).min().unwrap();
+
+        assert_eq!(pierwsza, 30);
+        assert!(wynik.nadplaty.wartosc(30) > Kwota::from(30.0 * 1_000.0));
+        assert!(wynik.koszt_kredytu < wynik.koszt_bez_nadplat);
+    }
+
+    #[test]
+    fn niska_prowizja_nie_wstrzymuje_nadplat() {
+        let wynik = optymalizacja(0.5);
+
+        assert!(wynik.nadplaty.wartosc(0) > Kwota::ZERO);
+        assert!(wynik.liczba_rat < wynik.liczba_rat_bez_nadplat);
+    }
+
+    #[test]
+    fn rata_z_nadplata_miesci_sie_w_budzecie_po_podwyzce_oprocentowania() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        kredyt.nadplaty = Nadplaty::default();
+        kredyt.zmiany_oprocentowania.push(ZmianaOprocentowania { od: 18, oprocentowanie: 9.0 });
+        let budzet = Kwota::from(6_000.0);
+        let optymalizacja = Optymalizacja { oferta: String::new(), budzet, cel: Cel::Koszt, segment: 12 };
+
+        let wynik = optymalizacja.optymalizuj(kredyt.clone()).unwrap();
+        kredyt.nadplaty = wynik.nadplaty;
+        let kalkulator = Kalkulator::new(kredyt).unwrap();
+
+        for (numer_raty, rata) in kalkulator.mapa_rat().iter() {
+            assert!(rata.wartosc() + rata.nadplata <= budzet, "rata {}: {} + {}", numer_raty, rata.wartosc(), rata.nadplata);
+        }
+
+        // po podwyzce nadwyzka jest liczona od nowej raty i wykorzystuje caly budzet
+        let podwyzka = kalkulator.mapa_rat()[&18];
+        assert!(podwyzka.nadplata > Kwota::ZERO);
+        assert_eq!(podwyzka.wartosc() + podwyzka.nadplata, budzet);
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, Koszt, KosztKoncowy, kredyt::Kredyt, Kwota};
+
+/// Premia od banku (cashback, zwrot oplaty za wycene) - koszt z przeciwnym znakiem.
+/// Premia warunkowa jest wyplacana tylko wtedy, gdy spelniony jest warunek marzy albo
+/// posiadany jest produkt o nazwie `warunek`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Premia {
+    #[serde(flatten)]
+    pub koszt: Koszt,
+
+    #[serde(rename = "warunek", default)]
+    pub warunek: Option<String>,
+}
+
+impl Premia {
+    /// Raty `od..do`, w ktorych spelniony jest warunek premii - warunek marzy albo posiadanie produktu.
+    pub fn okres_warunku(&self, dto: &Kredyt, liczba_rat: u64) -> Result<(u64, u64), Blad> {
+        let nazwa = match &self.warunek {
+            Some(nazwa) => nazwa,
+            None => return Ok((0, liczba_rat)),
+        };
+        let (od_raty, do_raty) = dto.warunki_marzy.iter()
+            .find(|w| &w.nazwa == nazwa)
+            .map(|w| (w.spelniony_od, w.spelniony_do))
+            .or_else(|| dto.produkty.iter().find(|p| &p.nazwa == nazwa).map(|p| (0, p.rezygnacja)))
+            .ok_or_else(|| Blad::Dane(format!("premia `{}` wymaga nieznanego warunku `{}`", self.koszt.nazwa(), nazwa)))?;
+
+        Ok((od_raty, do_raty.map_or(liczba_rat, |r| r.min(liczba_rat))))
+    }
+
+    /// Premia jako ujemny koszt, przy operatorze `procent` liczona od kwoty kredytu - jak koszty oferty.
+    pub fn oblicz(&self, dto: &Kredyt, liczba_rat: u64) -> Result<KosztKoncowy, Blad> {
+        let (od_raty, do_raty) = self.okres_warunku(dto, liczba_rat)?;
+
+        if do_raty <= od_raty {
+            return Ok(KosztKoncowy::new(self.koszt.clone(), Kwota::ZERO, 0));
+        }
+
+        Ok(self.koszt.oblicz(dto.kwota_kredytowania(), do_raty - od_raty).od_raty(od_raty).premia())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Kalkulator;
+
+    #[test]
+    fn premia_za_produkt_bez_obnizki_marzy_do_rezygnacji() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_cashback.json").unwrap();
+        let konto = kredyt.produkty.iter_mut().find(|p| p.nazwa == "konto osobiste").unwrap();
+        konto.obnizka_marzy = 0.0;
+        konto.rezygnacja = Some(12);
+
+        let premia = kredyt.premie[1].oblicz(&kredyt, 300).unwrap();
+        assert_eq!(premia.total(), -Kwota::from(240.0));
+    }
+
+    #[test]
+    fn cashback_procentowy_od_kwoty_kredytu() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_cashback.json").unwrap();
+
+        assert_eq!(kredyt.premie[0].oblicz(&kredyt, 300).unwrap().total(), -Kwota::from(3_150.0));
+    }
+
+    #[test]
+    fn nieznany_warunek_premii_jest_bledem() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_cashback.json").unwrap();
+        kredyt.premie[1].warunek = Some("konto firmowe".to_string());
+
+        assert!(kredyt.premie[1].oblicz(&kredyt, 300).is_err());
+        assert!(Kalkulator::new(kredyt).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{Koszt, KosztKoncowy, Kwota, Okres, Operator, WarunekMarzy};
+
+/// Produkt wymagany przez bank (konto, karta, ubezpieczenie domu). Oplata produktu jest kosztem
+/// kredytu, a jego posiadanie obniza marze o `obnizka_marzy` punktow procentowych.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Produkt {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    /// Oplata za produkt, przy operatorze `procent` - w procentach wartosci nieruchomosci.
+    #[serde(rename = "wartosc", default)]
+    pub wartosc: f64,
+
+    #[serde(rename = "operator", default = "Produkt::domyslny_operator")]
+    pub operator: Operator,
+
+    #[serde(rename = "okres", default = "Produkt::domyslny_okres")]
+    pub okres: Okres,
+
+    /// Oplata nie jest pobierana bez dodatkowego kosztu, np. konto z wplywem wynagrodzenia.
+    #[serde(rename = "zwolniony", default)]
+    pub zwolniony: bool,
+
+    /// Oplata nie jest pobierana przy minimalnych miesiecznych wydatkach, np. transakcjach karta.
+    #[serde(rename = "minimalne_wydatki", default)]
+    pub minimalne_wydatki: Option<MinimalneWydatki>,
+
+    #[serde(rename = "obnizka_marzy", default)]
+    pub obnizka_marzy: f64,
+
+    /// Rata, od ktorej produkt nie jest juz posiadany - konczy sie oplata i obnizka marzy.
+    #[serde(rename = "rezygnacja", default)]
+    pub rezygnacja: Option<u64>,
+}
+
+/// Minimalne miesieczne wydatki zwalniajace z oplaty. Kosztem jest `procent_kosztu` procent
+/// wydatkow, ktorych kredytobiorca nie ponioslby bez produktu (dla wydatkow i tak planowanych - 0).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MinimalneWydatki {
+    #[serde(rename = "kwota")]
+    pub kwota: Kwota,
+
+    #[serde(rename = "procent_kosztu", default = "MinimalneWydatki::domyslny_procent_kosztu")]
+    pub procent_kosztu: f64,
+}
+
+impl MinimalneWydatki {
+    fn domyslny_procent_kosztu() -> f64 { 100.0 }
+
+    pub fn koszt(&self) -> Kwota {
+        self.kwota * (self.procent_kosztu / 100.0)
+    }
+}
+
+impl Produkt {
+    fn domyslny_operator() -> Operator { Operator::Stala }
+
+    fn domyslny_okres() -> Okres { Okres::Miesieczny }
+
+    /// Oplata za produkt (albo koszt minimalnych wydatkow zwalniajacych z oplaty) do rezygnacji
+    /// albo splaty kredytu; `None`, gdy produkt jest bezplatny.
+    pub fn koszt(&self, wartosc_hipoteki: Kwota, liczba_rat: u64) -> Option<KosztKoncowy> {
+        if self.zwolniony {
+            return None;
+        }
+
+        let koszt = match &self.minimalne_wydatki {
+            Some(wydatki) => Koszt::builder()
+                .nazwa(self.nazwa.clone())
+                .wartosc(wydatki.koszt().zl())
+                .operator(Operator::Stala)
+                .okres(Okres::Miesieczny)
+                .build(),
+            None => Koszt::builder()
+                .nazwa(self.nazwa.clone())
+                .wartosc(self.wartosc)
+                .operator(self.operator.clone())
+                .okres(self.okres)
+                .build(),
+        };
+
+        if koszt.wartosc() == 0.0 {
+            return None;
+        }
+
+        Some(koszt.oblicz(wartosc_hipoteki, self.rezygnacja.map_or(liczba_rat, |r| r.min(liczba_rat))))
+    }
+
+    /// Obnizka marzy jako warunek, ktory przestaje byc spelniony po rezygnacji z produktu.
+    pub fn warunek_marzy(&self) -> Option<WarunekMarzy> {
+        if self.obnizka_marzy == 0.0 {
+            return None;
+        }
+
+        Some(WarunekMarzy {
+            nazwa: self.nazwa.clone(),
+            podwyzka_marzy: self.obnizka_marzy,
+            spelniony_od: 0,
+            spelniony_do: self.rezygnacja,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kalkulator, Kredyt};
+
+    #[test]
+    fn minimalne_wydatki_zastepuja_oplate_za_karte() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_produkty.json").unwrap();
+        let karta = kredyt.produkty.iter_mut().find(|p| p.nazwa == "karta kredytowa").unwrap();
+        karta.minimalne_wydatki = Some(MinimalneWydatki { kwota: Kwota::from(500.0), procent_kosztu: 10.0 });
+
+        let koszt = karta.koszt(kredyt.wartosc_hipoteki, 12).unwrap();
+        assert_eq!(koszt.total(), Kwota::from(600.0));
+
+        karta.minimalne_wydatki.as_mut().unwrap().procent_kosztu = 0.0;
+        assert!(karta.koszt(kredyt.wartosc_hipoteki, 12).is_none());
+    }
+
+    #[test]
+    fn produkt_o_nazwie_kosztu_oferty_jest_bledem() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_produkty.json").unwrap();
+        kredyt.produkty[1].nazwa = "ubezpieczenie zycia".to_string();
+
+        assert!(kredyt.sprawdz().is_err());
+        assert!(Kalkulator::new(kredyt).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use crate::{Blad, kredyt::Kredyt, KalkulatorRaty, Kwota};
+
+/// Roznica oprocentowania (w punktach procentowych), powyzej ktorej rata banku nie zgadza sie
+/// z deklarowanym oprocentowaniem.
+pub const TOLERANCJA_OPROCENTOWANIA: f64 = 0.01;
+
+/// Oprocentowanie odczytane z raty podanej przez bank, porownane z deklarowanym w ofercie.
+/// Rozbieznosc moze wynikac z innego zaokraglania, innej liczby dni w roku albo ukrytych kosztow.
+pub struct OprocentowanieZRaty {
+    pub nazwa: String,
+    pub oprocentowanie: f64,
+    pub rata: Kwota,
+    pub rata_banku: Kwota,
+    pub oprocentowanie_z_raty: f64,
+}
+
+impl OprocentowanieZRaty {
+    /// Porownanie z pierwsza rata harmonogramu oferty - z promocyjna marza, warunkami marzy,
+    /// karencja, balonem i transzami. Oprocentowanie z raty banku jest szukane bisekcja po tym
+    /// samym harmonogramie, bo pierwsza rata rosnie wraz z oprocentowaniem. Rata banku spoza
+    /// zakresu pierwszych rat dla oprocentowania 0% - 100% jest bledem danych.
+    pub fn oblicz(kredyt: &Kredyt) -> Result<Option<Self>, Blad> {
+        let rata_banku = match kredyt.rata_banku {
+            Some(rata_banku) => rata_banku,
+            None => return Ok(None),
+        };
+
+        // oprocentowanie pierwszej raty ustawiane przez oprocentowanie bazowe, marza jak w ofercie
+        let mut wzor = kredyt.clone();
+        wzor.zmiany_oprocentowania.retain(|z| z.od > 0);
+        wzor.oprocentowanie = 0.0;
+        let marza = wzor.oprocentowanie_w_racie(0);
+        let pierwsza_rata = |oprocentowanie: f64| {
+            let mut kredyt = wzor.clone();
+            kredyt.oprocentowanie = oprocentowanie - marza;
+
+            KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat()
+                .values()
+                .next()
+                .map(|r| r.wartosc())
+                .unwrap_or_default()
+        };
+
+        let (mut dolne, mut gorne) = (0.0, 100.0);
+
+        if rata_banku < pierwsza_rata(dolne) || rata_banku > pierwsza_rata(gorne) {
+            return Err(Blad::Dane(format!("rata banku {:.2}zl oferty `{}` nie odpowiada oprocentowaniu 0% - 100%", rata_banku, kredyt.nazwa)));
+        }
+
+        for _ in 0..100 {
+            let srodek = (dolne + gorne) / 2.0;
+
+            if pierwsza_rata(srodek) < rata_banku {
+                dolne = srodek;
+            } else {
+                gorne = srodek;
+            }
+        }
+
+        let oprocentowanie = kredyt.oprocentowanie_w_racie(0);
+
+        Ok(Some(Self {
+            nazwa: kredyt.nazwa.clone(),
+            oprocentowanie,
+            rata: pierwsza_rata(oprocentowanie),
+            rata_banku,
+            oprocentowanie_z_raty: (dolne + gorne) / 2.0,
+        }))
+    }
+
+    /// Roznica w punktach procentowych, dodatnia gdy rata banku jest wyzsza niz wynika z oferty.
+    pub fn roznica(&self) -> f64 {
+        self.oprocentowanie_z_raty - self.oprocentowanie
+    }
+
+    pub fn rozbieznosc(&self) -> bool {
+        self.roznica().abs() > TOLERANCJA_OPROCENTOWANIA
+    }
+}
+
+impl fmt::Display for OprocentowanieZRaty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Rata banku `{}`: {:.2}zl, wg oprocentowania {:.2}%: {:.2}zl",
+            self.nazwa, self.rata_banku, self.oprocentowanie, self.rata
+        )?;
+        write!(f, "Rata banku odpowiada oprocentowaniu {:.3}% ({:+.3} pp.)", self.oprocentowanie_z_raty, self.roznica())?;
+
+        if self.rozbieznosc() {
+            write!(f, " - UWAGA: rata nie zgadza sie z deklarowanym oprocentowaniem, mozliwe ukryte koszty")?;
+        }
+
+        writeln!(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rata_banku(oferta: &str, rata: f64) -> Result<Option<OprocentowanieZRaty>, Blad> {
+        let mut kredyt = Kredyt::wczytaj(oferta).unwrap();
+        kredyt.rata_banku = Some(Kwota::from(rata));
+
+        OprocentowanieZRaty::oblicz(&kredyt)
+    }
+
+    #[test]
+    fn rata_wg_oferty_nie_jest_rozbiezna() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        let rata = KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat()[&0].wartosc();
+        let wynik = rata_banku("data/kredyt/pekao_sa_25.json", rata.zl()).unwrap().unwrap();
+
+        assert!(!wynik.rozbieznosc(), "roznica {}", wynik.roznica());
+    }
+
+    #[test]
+    fn promocyjna_marza_porownywana_z_pierwsza_rata() {
+        let wynik = rata_banku("data/kredyt/pekao_sa_25_promocja.json", 4_000.0).unwrap().unwrap();
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_promocja.json").unwrap();
+
+        assert!((wynik.oprocentowanie - 5.94).abs() < 1e-9);
+        assert_eq!(wynik.rata, KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat()[&0].wartosc());
+    }
+
+    #[test]
+    fn rata_spoza_zakresu_oprocentowania_jest_bledem() {
+        assert!(rata_banku("data/kredyt/pekao_sa_25.json", 100.0).is_err());
+        assert!(rata_banku("data/kredyt/pekao_sa_25.json", 1_000_000.0).is_err());
+    }
+
+    #[test]
+    fn rata_odsetkowa_kredytu_na_budowe() {
+        // pierwsza rata budowy to odsetki od pierwszej transzy - nie rata rowna calej kwoty
+        let kredyt = Kredyt::wczytaj("data/kredyt/mbank_budowa.json").unwrap();
+        let rata = KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat()[&0].wartosc();
+        let wynik = rata_banku("data/kredyt/mbank_budowa.json", rata.zl()).unwrap().unwrap();
+
+        assert_eq!(wynik.rata, rata);
+        assert!(!wynik.rozbieznosc(), "roznica {}", wynik.roznica());
+
+        let wynik = rata_banku("data/kredyt/mbank_budowa.json", rata.zl() * 1.1).unwrap().unwrap();
+        assert!(wynik.rozbieznosc());
+    }
+
+    #[test]
+    fn rata_tylko_odsetkowa() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_pomostowy.json").unwrap();
+        let odsetki = kredyt.kwota_kredytowania() * (6.5 / 100.0 / 12.0);
+        let wynik = rata_banku("data/kredyt/pekao_sa_pomostowy.json", odsetki.zl()).unwrap().unwrap();
+
+        assert!((wynik.oprocentowanie_z_raty - 6.5).abs() < 0.001, "oprocentowanie {}", wynik.oprocentowanie_z_raty);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+use typed_builder::TypedBuilder;
+
+use crate::{kredyt::Kredyt, KalkulatorRaty, Koszt, KosztKoncowy, Kwota, Okres, Operator, Rata};
+
+/// Przeniesienie kredytu do innego banku: od raty `od_raty` pozostaly kapital jest splacany
+/// na warunkach nowej oferty. Koszty procentowe liczone sa od przenoszonego kapitalu, od ktorego
+/// pobierana jest tez prowizja starego banku (`prowizja_za_nadplate`), jesli przeniesienie wypada przed `do_raty`.
+/// Kredyt walutowy pozostaje w nowym banku w tej samej walucie - przewalutowanie nie jest obslugiwane.
+#[derive(Clone, Serialize, Deserialize, TypedBuilder)]
+pub struct Refinansowanie {
+    #[serde(rename = "nazwa")]
+    #[builder(setter(into))]
+    pub nazwa: String,
+
+    #[serde(rename = "od_raty")]
+    pub od_raty: u64,
+
+    #[serde(rename = "oprocentowanie")]
+    pub oprocentowanie: f64,
+
+    #[serde(rename = "okres_kredytowania")]
+    pub okres_kredytowania: u64,
+
+    #[serde(rename = "koszty", default)]
+    #[builder(default)]
+    pub koszty: Vec<Koszt>,
+}
+
+impl Refinansowanie {
+    pub fn mapa_rat(&self, kapital_do_splaty: Kwota, dto: &Kredyt) -> BTreeMap<u64, Rata> {
+        KalkulatorRaty::new(kapital_do_splaty, self.oprocentowanie, self.okres_kredytowania, dto.wszystkie_nadplaty())
+            .od_raty(self.od_raty)
+            .zaokraglenie(dto.zaokraglenie)
+            .mapa_rat()
+    }
+
+    pub fn mapa_kosztow(&self, kapital_do_splaty: Kwota, liczba_rat: u64, dto: &Kredyt) -> BTreeMap<String, KosztKoncowy> {
+        let mut koszty: BTreeMap<String, KosztKoncowy> = self.koszty.iter()
+            .map(|k| {
+                let nazwa = format!("{} ({})", k.nazwa(), self.nazwa);
+                let koszt = k.clone().przemianuj(nazwa.clone()).oblicz(kapital_do_splaty, liczba_rat).od_raty(self.od_raty);
+
+                (nazwa, koszt)
+            })
+            .collect();
+
+        if let Some(prowizja) = dto.prowizja_za_nadplate.as_ref().filter(|p| self.od_raty < p.do_raty) {
+            let nazwa = format!("prowizja za splate ({})", dto.nazwa);
+            let koszt = Koszt::builder()
+                .nazwa(nazwa.clone())
+                .wartosc(prowizja.procent)
+                .operator(Operator::Procent)
+                .okres(Okres::Jednorazowy)
+                .build()
+                .oblicz(kapital_do_splaty, liczba_rat)
+                .od_raty(self.od_raty);
+
+            koszty.insert(nazwa, koszt);
+        }
+
+        koszty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kalkulator, ProwizjaZaNadplate};
+
+    fn prowizja_za_splate(do_raty: u64) -> Option<(Kwota, Kwota)> {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_refinansowanie.json").unwrap();
+        kredyt.prowizja_za_nadplate = Some(ProwizjaZaNadplate { procent: 3.0, do_raty });
+        let kalkulator = Kalkulator::new(kredyt).unwrap();
+        let przeniesiony_kapital = kalkulator.kwota_kredytowania()
+            - kalkulator.mapa_rat().range(..36).map(|(_, r)| r.kapital()).sum::<Kwota>();
+
+        kalkulator.mapa_kosztow().get("prowizja za splate (Pekao SA 25 -> Santander)")
+            .map(|k| (k.total(), przeniesiony_kapital * (3.0 / 100.0)))
+    }
+
+    #[test]
+    fn przeniesienie_w_okresie_prowizji_placi_prowizje_od_kapitalu() {
+        let (prowizja, oczekiwana) = prowizja_za_splate(60).unwrap();
+
+        assert_eq!(prowizja, oczekiwana);
+        assert!(prowizja_za_splate(36).is_none());
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, Kwota, Nadplata, Nadplaty};
+
+/// Zakres wartosci parametru: jawna lista albo `od`-`do` z krokiem.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Zakres {
+    Lista(Vec<f64>),
+    Krok {
+        od: f64,
+        #[serde(rename = "do")]
+        to: f64,
+        krok: f64,
+    },
+}
+
+impl Zakres {
+    pub fn wartosci(&self) -> Result<Vec<f64>, Blad> {
+        match self {
+            Zakres::Lista(wartosci) => Ok(wartosci.clone()),
+            Zakres::Krok { krok, .. } if *krok <= 0.0 => {
+                Err(Blad::Dane(format!("krok zakresu musi byc dodatni, podano {}", krok)))
+            },
+            Zakres::Krok { od, to, .. } if to < od => {
+                Err(Blad::Dane(format!("koniec zakresu {} jest mniejszy niz poczatek {}", to, od)))
+            },
+            Zakres::Krok { od, to, krok } => {
+                let krokow = ((to - od) / krok + 1e-9).floor() as u64;
+
+                Ok((0..=krokow).map(|i| od + krok * i as f64).collect())
+            }
+        }
+    }
+
+    /// Wartosci zakresu jako liczby rat - dodatnie liczby calkowite.
+    pub fn liczby_rat(&self) -> Result<Vec<u64>, Blad> {
+        self.wartosci()?.into_iter()
+            .map(|wartosc| match wartosc.round() {
+                calkowita if calkowita >= 1.0 && (wartosc - calkowita).abs() < 1e-9 => Ok(calkowita as u64),
+                _ => Err(Blad::Dane(format!("liczba rat musi byc dodatnia liczba calkowita, podano {}", wartosc))),
+            })
+            .collect()
+    }
+}
+
+/// Parametry, ktore nie zostaly podane, przyjmuja wartosc z oferty bazowej.
+#[derive(Serialize, Deserialize)]
+pub struct Siatka {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "okres_kredytowania", default)]
+    pub okres_kredytowania: Option<Zakres>,
+
+    #[serde(rename = "oprocentowanie", default)]
+    pub oprocentowanie: Option<Zakres>,
+
+    #[serde(rename = "wklad_wlasny", default)]
+    pub wklad_wlasny: Option<Zakres>,
+
+    /// Stala miesieczna nadplata przez caly okres kredytowania, zastepuje nadplaty z oferty.
+    #[serde(rename = "nadplaty", default)]
+    pub nadplaty: Option<Zakres>,
+}
+
+pub struct Wiersz {
+    pub okres_kredytowania: u64,
+    pub oprocentowanie: f64,
+    pub wklad_wlasny: f64,
+    pub nadplata: Option<f64>,
+    pub rata: Kwota,
+    pub koszt_kredytu: Kwota,
+}
+
+pub struct WynikSiatki {
+    nazwa: String,
+    wiersze: Vec<Wiersz>,
+}
+
+impl Siatka {
+    pub fn oblicz(&self) -> Result<WynikSiatki, Blad> {
+        let bazowa = Kredyt::wczytaj(&self.oferta)?;
+        let wartosci = |zakres: &Option<Zakres>, domyslna: f64| {
+            zakres.as_ref().map_or(Ok(vec![domyslna]), Zakres::wartosci)
+        };
+        let nadplaty: Vec<Option<f64>> = match &self.nadplaty {
+            Some(zakres) => zakres.wartosci()?.into_iter().map(Some).collect(),
+            None => vec![None],
+        };
+
+        let okresy = match &self.okres_kredytowania {
+            Some(zakres) => zakres.liczby_rat()?,
+            None => vec![bazowa.okres_kredytowania],
+        };
+        let mut wiersze = Vec::new();
+
+        for okres_kredytowania in okresy {
+            for &oprocentowanie in wartosci(&self.oprocentowanie, bazowa.oprocentowanie)?.iter() {
+                for &wklad_wlasny in wartosci(&self.wklad_wlasny, bazowa.wklad_wlasny)?.iter() {
+                    for &nadplata in nadplaty.iter() {
+                        let mut kredyt = bazowa.clone();
+                        kredyt.okres_kredytowania = okres_kredytowania;
+                        kredyt.oprocentowanie = oprocentowanie;
+                        kredyt.wklad_wlasny = wklad_wlasny;
+
+                        if let Some(wartosc) = nadplata {
+                            kredyt.nadplaty = Nadplaty::new(vec![Nadplata {
+                                wartosc: Kwota::from(wartosc),
+                                from: 0,
+                                to: kredyt.okres_kredytowania,
+                                po_okresie: false,
+                            }]);
+                        }
+
+                        let kalkulator = Kalkulator::new(kredyt)?;
+
+                        wiersze.push(Wiersz {
+                            okres_kredytowania,
+                            oprocentowanie,
+                            wklad_wlasny,
+                            nadplata,
+                            rata: kalkulator.mapa_rat().values().next().map(|r| r.wartosc()).unwrap_or_default(),
+                            koszt_kredytu: kalkulator.koszt_kredytu(None),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(WynikSiatki { nazwa: bazowa.nazwa, wiersze })
+    }
+}
+
+impl WynikSiatki {
+    pub fn wiersze(&self) -> &[Wiersz] {
+        &self.wiersze
+    }
+}
+
+impl fmt::Display for WynikSiatki {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Siatka scenariuszy dla oferty `{}`:", self.nazwa)?;
+        writeln!(f, "{:>6} | {:>14} | {:>12} | {:>9} | {:>10} | {:>14}", "okres", "oprocentowanie", "wklad wlasny", "nadplata", "rata", "koszt kredytu")?;
+
+        for w in self.wiersze.iter() {
+            let nadplata = w.nadplata.map(|n| format!("{:.2}", n)).unwrap_or_else(|| "oferta".to_string());
+
+            writeln!(
+                f,
+                "{:>6} | {:>13.2}% | {:>11.2}% | {:>9} | {:>10.2} | {:>14.2}",
+                w.okres_kredytowania, w.oprocentowanie, w.wklad_wlasny, nadplata, w.rata, w.koszt_kredytu
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wczytaj_json;
+
+    #[test]
+    fn zakres_z_krokiem_obejmuje_koniec() {
+        let zakres = Zakres::Krok { od: 5.0, to: 8.0, krok: 1.0 };
+
+        assert_eq!(zakres.wartosci().unwrap(), vec![5.0, 6.0, 7.0, 8.0]);
+    }
+
+    #[test]
+    fn niepoprawny_zakres_jest_bledem() {
+        assert!(Zakres::Krok { od: 5.0, to: 8.0, krok: 0.0 }.wartosci().is_err());
+        assert!(Zakres::Krok { od: 5.0, to: 8.0, krok: -1.0 }.wartosci().is_err());
+        assert!(Zakres::Krok { od: 8.0, to: 5.0, krok: 1.0 }.wartosci().is_err());
+    }
+
+    #[test]
+    fn siatka_obejmuje_wszystkie_kombinacje_parametrow() {
+        let siatka: Siatka = wczytaj_json("data/siatka/pekao_sa_25.json").unwrap();
+        let wynik = siatka.oblicz().unwrap();
+
+        assert_eq!(wynik.wiersze().len(), 4 * 4 * 2);
+        assert!(wynik.wiersze().iter().all(|w| w.rata > Kwota::ZERO));
+    }
+
+    #[test]
+    fn okres_musi_byc_dodatnia_liczba_calkowita() {
+        assert_eq!(Zakres::Krok { od: 120.0, to: 360.0, krok: 120.0 }.liczby_rat().unwrap(), vec![120, 240, 360]);
+        assert!(Zakres::Lista(vec![300.7]).liczby_rat().is_err());
+        assert!(Zakres::Lista(vec![-12.0]).liczby_rat().is_err());
+        assert!(Zakres::Lista(vec![0.0]).liczby_rat().is_err());
+    }
+}
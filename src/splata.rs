@@ -0,0 +1,102 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::Kwota;
+
+/// Sposob splaty kapitalu. Poza ratami rownymi, np. przy kredycie pomostowym miedzy sprzedaza
+/// a zakupem, splacane sa tylko odsetki albo wieksza czesc kapitalu wraca w ostatniej racie.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum StrukturaSplaty {
+    #[default]
+    RowneRaty,
+    /// Przez pierwsze `miesiecy` rat splacane sa tylko odsetki, potem raty rowne. Bez `miesiecy`
+    /// odsetki sa splacane przez caly okres, a kapital w ostatniej racie.
+    TylkoOdsetki {
+        #[serde(default)]
+        miesiecy: Option<u64>,
+    },
+    /// `procent` kwoty kredytu jest splacany w ostatniej racie (rata balonowa), reszta w ratach rownych.
+    Balon { procent: f64 },
+}
+
+impl StrukturaSplaty {
+    /// Czesc kapitalu splacana w ostatniej racie.
+    pub fn balon(&self, kwota_kredytowania: Kwota) -> Kwota {
+        match self {
+            StrukturaSplaty::TylkoOdsetki { miesiecy: None } => kwota_kredytowania,
+            StrukturaSplaty::Balon { procent } => kwota_kredytowania * (procent / 100.0),
+            _ => Kwota::ZERO,
+        }
+    }
+
+    /// Liczba pierwszych rat, w ktorych splacane sa tylko odsetki.
+    pub fn karencja(&self, okres_kredytowania: u64) -> u64 {
+        match self {
+            StrukturaSplaty::TylkoOdsetki { miesiecy } => miesiecy.unwrap_or(okres_kredytowania),
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for StrukturaSplaty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StrukturaSplaty::RowneRaty => write!(f, "raty rowne"),
+            StrukturaSplaty::TylkoOdsetki { miesiecy: Some(miesiecy) } => write!(f, "tylko odsetki przez {} rat, potem raty rowne", miesiecy),
+            StrukturaSplaty::TylkoOdsetki { miesiecy: None } => write!(f, "tylko odsetki, kapital w ostatniej racie"),
+            StrukturaSplaty::Balon { procent } => write!(f, "rata balonowa {}% kwoty kredytu", procent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Kalkulator, KalkulatorRaty, Kredyt, Kwota};
+
+    fn harmonogram(oferta: &str) -> (Kredyt, Vec<(u64, Kwota)>) {
+        let kredyt = Kredyt::wczytaj(oferta).unwrap();
+        let raty = KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat()
+            .into_iter()
+            .map(|(n, r)| (n, r.kapital))
+            .collect();
+
+        (kredyt, raty)
+    }
+
+    #[test]
+    fn tylko_odsetki_splaca_kapital_w_ostatniej_racie() {
+        let (kredyt, raty) = harmonogram("data/kredyt/pekao_sa_pomostowy.json");
+        let (ostatnia, poprzednie) = raty.split_last().unwrap();
+
+        assert_eq!(raty.len() as u64, kredyt.okres_kredytowania);
+        assert!(poprzednie.iter().all(|&(_, kapital)| kapital == Kwota::ZERO));
+        assert_eq!(ostatnia.1, kredyt.kwota_kredytowania());
+    }
+
+    #[test]
+    fn balon_zostawia_zadany_procent_kapitalu_na_ostatnia_rate() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25_balon.json").unwrap();
+        let raty: Vec<_> = KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat().into_values().collect();
+        let balon = kredyt.kwota_kredytowania() * 0.3;
+
+        // ostatnia rata to zwykla rata rowna powiekszona o balon
+        let [.., przedostatnia, ostatnia] = raty.as_slice() else { panic!("za malo rat") };
+        let nadwyzka = ostatnia.wartosc() - przedostatnia.wartosc();
+
+        assert_eq!(raty.len() as u64, kredyt.okres_kredytowania);
+        assert!((nadwyzka.zl() - balon.zl()).abs() < 1.0, "ostatnia rata wieksza o {} zamiast {}", nadwyzka, balon);
+        assert!(raty[..raty.len() - 1].iter().all(|r| r.wartosc() == raty[0].wartosc()));
+    }
+
+    #[test]
+    fn budowa_splaca_odsetki_do_karencji_i_caly_kapital() {
+        let (kredyt, raty) = harmonogram("data/kredyt/mbank_budowa.json");
+        let karencja = kredyt.budowa.as_ref().unwrap().karencja();
+
+        assert!(raty.iter().filter(|&&(n, _)| n < karencja).all(|&(_, kapital)| kapital == Kwota::ZERO));
+        assert!(raty.iter().filter(|&&(n, _)| n >= karencja).all(|&(_, kapital)| kapital > Kwota::ZERO));
+        assert_eq!(raty.iter().map(|&(_, kapital)| kapital).sum::<Kwota>(), Kalkulator::new(kredyt).unwrap().kwota_kredytowania());
+    }
+}
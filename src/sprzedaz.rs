@@ -0,0 +1,160 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, Koszt, Kwota};
+
+/// Stawka podatku dochodowego od sprzedazy nieruchomosci, w procentach.
+pub const PODATEK_OD_SPRZEDAZY: f64 = 19.0;
+
+/// Sprzedaz nieruchomosci po `miesiac` ratach i splata pozostalego kapitalu. Podatek dochodowy
+/// jest naliczany, gdy sprzedaz nastepuje przed uplywem 5 lat liczonych od konca roku zakupu.
+#[derive(Serialize, Deserialize)]
+pub struct Sprzedaz {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "miesiac")]
+    pub miesiac: u64,
+
+    /// Miesiac kalendarzowy zakupu (1-12).
+    #[serde(rename = "miesiac_zakupu", default = "Sprzedaz::domyslny_miesiac_zakupu")]
+    pub miesiac_zakupu: u64,
+
+    /// Cena sprzedazy, domyslnie wartosc nieruchomosci z `zmiana_wartosci` oferty.
+    #[serde(rename = "cena", default)]
+    pub cena: Option<Kwota>,
+
+    /// Koszty procentowe sa liczone od ceny sprzedazy.
+    #[serde(rename = "koszty_sprzedazy", default)]
+    pub koszty_sprzedazy: Vec<Koszt>,
+}
+
+pub struct WynikSprzedazy {
+    pub nazwa: String,
+    pub miesiac: u64,
+    pub cena: Kwota,
+    pub koszty_sprzedazy: Kwota,
+    pub kapital_do_splaty: Kwota,
+    pub prowizja_za_splate: Kwota,
+    pub podatek: Kwota,
+    /// Wklad wlasny, raty z nadplatami i koszty kredytu do dnia sprzedazy.
+    pub wplacono: Kwota,
+}
+
+impl Sprzedaz {
+    fn domyslny_miesiac_zakupu() -> u64 { 1 }
+
+    /// Czy sprzedaz przypada przed koncem piatego roku kalendarzowego po roku zakupu.
+    pub fn podlega_podatkowi(&self) -> bool {
+        (self.miesiac_zakupu.clamp(1, 12) - 1 + self.miesiac) / 12 <= 5
+    }
+
+    pub fn oblicz(&self) -> Result<WynikSprzedazy, Blad> {
+        let kredyt = Kredyt::wczytaj(&self.oferta)?;
+        let prowizja = kredyt.prowizja_za_nadplate.clone();
+        let kalkulator = Kalkulator::new(kredyt)?;
+
+        let cena = self.cena.unwrap_or_else(|| kalkulator.wartosc_nieruchomosci(self.miesiac));
+        let koszty_sprzedazy: Kwota = self.koszty_sprzedazy.iter().map(|k| k.oblicz(cena, 1).total()).sum();
+        let kapital_do_splaty = kalkulator.kapital_do_splaty(self.miesiac).max(Kwota::ZERO);
+        let prowizja_za_splate = prowizja
+            .filter(|p| self.miesiac < p.do_raty)
+            .map_or(Kwota::ZERO, |p| kapital_do_splaty * (p.procent / 100.0));
+
+        let koszty_kredytu = kalkulator.mapa_kosztow().values();
+        let pcc = kalkulator.mapa_kosztow().get("PCC").map(|k| k.total()).unwrap_or_default();
+        let dochod = cena - koszty_sprzedazy - kalkulator.wartosc_hipoteki() - pcc;
+        let podatek = if self.podlega_podatkowi() && dochod > Kwota::ZERO {
+            dochod * (PODATEK_OD_SPRZEDAZY / 100.0)
+        } else {
+            Kwota::ZERO
+        };
+
+        let wplacono = kalkulator.wartosc_hipoteki() - kalkulator.kwota_kredytowania()
+            + kalkulator.mapa_rat().range(0..self.miesiac).map(|(_, r)| r.wplata()).sum::<Kwota>()
+            + koszty_kredytu.map(|k| k.do_raty(self.miesiac)).sum::<Kwota>();
+
+        Ok(WynikSprzedazy {
+            nazwa: kalkulator.nazwa().to_string(),
+            miesiac: self.miesiac,
+            cena,
+            koszty_sprzedazy,
+            kapital_do_splaty,
+            prowizja_za_splate,
+            podatek,
+            wplacono,
+        })
+    }
+}
+
+impl WynikSprzedazy {
+    /// Gotowka otrzymana ze sprzedazy po splacie kredytu, kosztach i podatku.
+    pub fn gotowka_ze_sprzedazy(&self) -> Kwota {
+        self.cena - self.koszty_sprzedazy - self.kapital_do_splaty - self.prowizja_za_splate - self.podatek
+    }
+
+    /// Wynik gotowkowy calego okresu posiadania nieruchomosci.
+    pub fn wynik(&self) -> Kwota {
+        self.gotowka_ze_sprzedazy() - self.wplacono
+    }
+}
+
+impl fmt::Display for WynikSprzedazy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Sprzedaz nieruchomosci (`{}`) po {} ratach:", self.nazwa, self.miesiac)?;
+        writeln!(f, "    * Cena: {:.2}zl, koszty sprzedazy: {:.2}zl", self.cena, self.koszty_sprzedazy)?;
+        writeln!(f, "    * Splata kredytu: {:.2}zl, prowizja za wczesniejsza splate: {:.2}zl", self.kapital_do_splaty, self.prowizja_za_splate)?;
+        writeln!(f, "    * Podatek dochodowy: {:.2}zl", self.podatek)?;
+        writeln!(f, "    * Gotowka ze sprzedazy: {:.2}zl", self.gotowka_ze_sprzedazy())?;
+        writeln!(f, "    * Wplacono (wklad wlasny, raty, koszty): {:.2}zl", self.wplacono)?;
+        writeln!(f, "Wynik okresu posiadania: {:.2}zl", self.wynik())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprzedaz(oferta: &str, miesiac: u64, cena: f64) -> Sprzedaz {
+        Sprzedaz {
+            oferta: oferta.to_string(),
+            miesiac,
+            miesiac_zakupu: 12,
+            cena: Some(Kwota::from(cena)),
+            koszty_sprzedazy: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn podatek_do_konca_piatego_roku_po_roku_zakupu() {
+        // zakup w grudniu - zwolnienie od stycznia szostego roku, czyli od 61. miesiaca
+        assert!(sprzedaz("data/kredyt/pekao_sa_25.json", 60, 0.0).podlega_podatkowi());
+        assert!(!sprzedaz("data/kredyt/pekao_sa_25.json", 61, 0.0).podlega_podatkowi());
+    }
+
+    #[test]
+    fn podatek_od_dochodu_ponad_cene_zakupu_i_pcc() {
+        let kalkulator = Kalkulator::new(Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap()).unwrap();
+        let pcc = kalkulator.mapa_kosztow()["PCC"].total();
+
+        let wynik = sprzedaz("data/kredyt/pekao_sa_25.json", 24, 800_000.0).oblicz().unwrap();
+        assert_eq!(wynik.podatek, (Kwota::from(100_000.0) - pcc) * (PODATEK_OD_SPRZEDAZY / 100.0));
+
+        let wynik = sprzedaz("data/kredyt/pekao_sa_25.json", 24, 650_000.0).oblicz().unwrap();
+        assert_eq!(wynik.podatek, Kwota::ZERO);
+
+        let wynik = sprzedaz("data/kredyt/pekao_sa_25.json", 72, 800_000.0).oblicz().unwrap();
+        assert_eq!(wynik.podatek, Kwota::ZERO);
+    }
+
+    #[test]
+    fn prowizja_za_splate_tylko_w_okresie_prowizji() {
+        let wynik = sprzedaz("data/kredyt/pekao_sa_25_prowizja.json", 12, 700_000.0).oblicz().unwrap();
+        assert_eq!(wynik.prowizja_za_splate, wynik.kapital_do_splaty * 0.02);
+
+        let wynik = sprzedaz("data/kredyt/pekao_sa_25_prowizja.json", 36, 700_000.0).oblicz().unwrap();
+        assert_eq!(wynik.prowizja_za_splate, Kwota::ZERO);
+        assert_eq!(wynik.gotowka_ze_sprzedazy(), Kwota::from(700_000.0) - wynik.kapital_do_splaty);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, marza::ZmianaMarzy, Gospodarstwo, Kwota};
+
+/// Test warunkowy oferty: wzrost oprocentowania o kolejne szoki (w punktach procentowych)
+/// od raty `od_raty`, zgodnie z buforem z Rekomendacji S.
+#[derive(Serialize, Deserialize)]
+pub struct TestStresu {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "szoki", default = "TestStresu::domyslne_szoki")]
+    pub szoki: Vec<f64>,
+
+    #[serde(rename = "od_raty", default)]
+    pub od_raty: u64,
+
+    /// Sciezka do pliku `Gospodarstwo`, z ktorego liczona jest miesieczna nadwyzka budzetu.
+    #[serde(rename = "gospodarstwo", default)]
+    pub gospodarstwo: Option<String>,
+}
+
+pub struct WierszStresu {
+    pub szok: f64,
+    pub oprocentowanie: f64,
+    pub rata: Kwota,
+    pub koszt_kredytu: Kwota,
+    /// Najnizsza miesieczna nadwyzka budzetu od raty `od_raty`, po racie i kosztach kredytu naliczanych w tym miesiacu.
+    pub nadwyzka: Option<Kwota>,
+}
+
+pub struct WynikTestuStresu {
+    nazwa: String,
+    od_raty: u64,
+    wiersze: Vec<WierszStresu>,
+}
+
+impl TestStresu {
+    fn domyslne_szoki() -> Vec<f64> {
+        vec![0.0, 1.0, 2.0, 3.0, 5.0]
+    }
+
+    /// Oferta z kazdym oprocentowaniem od raty `od_raty` (wraz z pozniejszymi zmianami) wyzszym o `szok`.
+    fn ze_szokiem(bazowa: &Kredyt, od_raty: u64, szok: f64) -> Kredyt {
+        let mut kredyt = bazowa.clone();
+        kredyt.zmiany_marzy.push(ZmianaMarzy { od: od_raty, to: None, zmiana: szok });
+
+        kredyt
+    }
+
+    fn najnizsza_nadwyzka(nadwyzka: Kwota, kalkulator: &Kalkulator, od_raty: u64) -> Kwota {
+        kalkulator.mapa_rat().range(od_raty..)
+            .map(|(&n, r)| {
+                let koszty: Kwota = kalkulator.mapa_kosztow().values().map(|k| k.miesiecznie_w_racie(n)).sum();
+
+                nadwyzka - r.wartosc() - koszty
+            })
+            .min()
+            .unwrap_or(nadwyzka)
+    }
+
+    pub fn oblicz(&self) -> Result<WynikTestuStresu, Blad> {
+        let bazowa = Kredyt::wczytaj(&self.oferta)?;
+        let nadwyzka = match self.gospodarstwo.as_deref() {
+            Some(path) => Some(Gospodarstwo::wczytaj(path)?.nadwyzka()),
+            None => None,
+        };
+
+        let wiersze = self.szoki.iter().map(|&szok| {
+            let kredyt = Self::ze_szokiem(&bazowa, self.od_raty, szok);
+            let oprocentowanie = kredyt.oprocentowanie_w_racie(self.od_raty);
+            let kalkulator = Kalkulator::new(kredyt)?;
+
+            Ok(WierszStresu {
+                szok,
+                oprocentowanie,
+                rata: kalkulator.mapa_rat().get(&self.od_raty).map(|r| r.wartosc()).unwrap_or_default(),
+                koszt_kredytu: kalkulator.koszt_kredytu(None),
+                nadwyzka: nadwyzka.map(|n| Self::najnizsza_nadwyzka(n, &kalkulator, self.od_raty)),
+            })
+        })
+        .collect::<Result<_, Blad>>()?;
+
+        Ok(WynikTestuStresu { nazwa: bazowa.nazwa, od_raty: self.od_raty, wiersze })
+    }
+}
+
+impl WynikTestuStresu {
+    pub fn wiersze(&self) -> &[WierszStresu] {
+        &self.wiersze
+    }
+}
+
+impl fmt::Display for WynikTestuStresu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Test warunkowy oferty `{}` (zmiana od {}. raty):", self.nazwa, self.od_raty + 1)?;
+
+        for w in self.wiersze.iter() {
+            write!(f, "    * +{:.2}pp ({:.2}%): rata {:.2}zl, koszt kredytu {:.2}zl", w.szok, w.oprocentowanie, w.rata, w.koszt_kredytu)?;
+
+            match w.nadwyzka {
+                Some(nadwyzka) if nadwyzka > Kwota::ZERO => writeln!(f, ", nadwyzka budzetu {:.2}zl", nadwyzka)?,
+                Some(nadwyzka) => writeln!(f, ", BRAK nadwyzki budzetu ({:.2}zl)", nadwyzka)?,
+                None => writeln!(f)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ZmianaOprocentowania;
+
+    #[test]
+    fn szok_przesuwa_pozniejsze_zmiany_oprocentowania() {
+        let mut bazowa = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        bazowa.zmiany_oprocentowania.push(ZmianaOprocentowania { od: 120, oprocentowanie: 4.0 });
+        let kredyt = TestStresu::ze_szokiem(&bazowa, 60, 2.0);
+
+        for n in [0, 59, 60, 119, 120, 299].iter().copied() {
+            let szok = if n < 60 { 0.0 } else { 2.0 };
+
+            assert!((kredyt.oprocentowanie_w_racie(n) - bazowa.oprocentowanie_w_racie(n) - szok).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn nadwyzka_pomija_wygasle_koszty() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        let kalkulator = Kalkulator::new(kredyt).unwrap();
+        let nadwyzka = Kwota::from(10_000.0);
+        let maksymalna_rata = kalkulator.mapa_rat().range(60..).map(|(_, r)| r.wartosc()).max().unwrap();
+        let koszty = kalkulator.mapa_kosztow().values().map(|k| k.miesiecznie_w_racie(60)).sum::<Kwota>();
+
+        // od 61. raty naliczane sa tylko ubezpieczenia, ubezpieczenie pomostowe wygaslo po 5 ratach
+        assert_eq!(koszty, Kwota::from(110.0) + Kwota::from(550.0) * (1.0 / 12.0));
+        assert_eq!(TestStresu::najnizsza_nadwyzka(nadwyzka, &kalkulator, 60), nadwyzka - maksymalna_rata - koszty);
+    }
+}
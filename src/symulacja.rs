@@ -0,0 +1,209 @@
+use std::{collections::BTreeMap, fmt};
+
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, StandardNormal};
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::{Kredyt, ZmianaOprocentowania}, KalkulatorRaty, Kwota, Rata};
+
+/// Stochastyczny model oprocentowania, parametry roczne w punktach procentowych.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum ModelStopy {
+    /// Powrot do sredniej (model Vasicka).
+    Vasicek {
+        srednia: f64,
+        tempo_powrotu: f64,
+        zmiennosc: f64,
+    },
+    BladzenieLosowe {
+        zmiennosc: f64,
+    },
+}
+
+impl ModelStopy {
+    fn krok(&self, stopa: f64, z: f64) -> f64 {
+        let dt = 1.0 / 12.0;
+
+        match *self {
+            ModelStopy::Vasicek { srednia, tempo_powrotu, zmiennosc } => {
+                stopa + tempo_powrotu * (srednia - stopa) * dt + zmiennosc * dt.sqrt() * z
+            },
+            ModelStopy::BladzenieLosowe { zmiennosc } => stopa + zmiennosc * dt.sqrt() * z,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Symulacja {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "model")]
+    pub model: ModelStopy,
+
+    #[serde(rename = "liczba_sciezek", default = "Symulacja::domyslna_liczba_sciezek")]
+    pub liczba_sciezek: usize,
+
+    #[serde(rename = "ziarno", default)]
+    pub ziarno: u64,
+
+    /// Co ile miesiecy bank aktualizuje oprocentowanie (np. 3 dla WIBOR 3M).
+    #[serde(rename = "okres_zmiany", default = "Symulacja::domyslny_okres_zmiany")]
+    pub okres_zmiany: u64,
+
+    #[serde(rename = "minimalne_oprocentowanie", default)]
+    pub minimalne_oprocentowanie: f64,
+
+    /// Miesieczny budzet na rate, do wyznaczenia prawdopodobienstwa jego przekroczenia.
+    #[serde(rename = "budzet", default)]
+    pub budzet: Option<Kwota>,
+}
+
+/// Percentyle P5 / P50 / P95 wynikow symulacji.
+#[derive(Copy, Clone, Debug)]
+pub struct Percentyle {
+    pub p5: Kwota,
+    pub p50: Kwota,
+    pub p95: Kwota,
+}
+
+impl Percentyle {
+    fn new(mut wartosci: Vec<Kwota>) -> Self {
+        wartosci.sort();
+        let percentyl = |p: f64| wartosci
+            .get(((wartosci.len().max(1) - 1) as f64 * p).round() as usize)
+            .copied()
+            .unwrap_or_default();
+
+        Self { p5: percentyl(0.05), p50: percentyl(0.5), p95: percentyl(0.95) }
+    }
+}
+
+impl fmt::Display for Percentyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "P5: {:.2}zl, P50: {:.2}zl, P95: {:.2}zl", self.p5, self.p50, self.p95)
+    }
+}
+
+pub struct WynikSymulacji {
+    pub nazwa: String,
+    pub liczba_sciezek: usize,
+    pub odsetki: Percentyle,
+    pub maksymalna_rata: Percentyle,
+    pub przekroczenie_budzetu: Option<f64>,
+}
+
+impl Symulacja {
+    fn domyslna_liczba_sciezek() -> usize { 1000 }
+
+    fn domyslny_okres_zmiany() -> u64 { 3 }
+
+    /// Sciezka oprocentowania: zmiany w kolejnych okresach aktualizacji, poczawszy od oprocentowania oferty.
+    fn sciezka(&self, kredyt: &Kredyt, rng: &mut StdRng) -> Vec<(u64, f64)> {
+        let mut stopa = kredyt.oprocentowanie;
+        let mut zmiany = Vec::new();
+
+        for numer_raty in 1..kredyt.okres_kredytowania {
+            stopa = self.model.krok(stopa, StandardNormal.sample(rng)).max(self.minimalne_oprocentowanie);
+
+            if numer_raty % self.okres_zmiany.max(1) == 0 {
+                zmiany.push((numer_raty, stopa));
+            }
+        }
+
+        zmiany
+    }
+
+    /// Harmonogram oferty, w ktorym wylosowana sciezka zastepuje bazowe oprocentowanie. Zmiany marzy,
+    /// warunki, karencja, balon i transze pozostaja jak w ofercie.
+    fn harmonogram(&self, kredyt: &Kredyt, rng: &mut StdRng) -> BTreeMap<u64, Rata> {
+        let mut kredyt = kredyt.clone();
+        kredyt.zmiany_oprocentowania = self.sciezka(&kredyt, rng).into_iter()
+            .map(|(od, oprocentowanie)| ZmianaOprocentowania { od, oprocentowanie })
+            .collect();
+
+        KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat()
+    }
+
+    pub fn oblicz(&self) -> Result<WynikSymulacji, Blad> {
+        let kredyt = Kredyt::wczytaj(&self.oferta)?;
+        let mut rng = StdRng::seed_from_u64(self.ziarno);
+        let mut odsetki = Vec::with_capacity(self.liczba_sciezek);
+        let mut maksymalne_raty = Vec::with_capacity(self.liczba_sciezek);
+        let mut przekroczenia = 0;
+
+        for _ in 0..self.liczba_sciezek {
+            let mapa_rat = self.harmonogram(&kredyt, &mut rng);
+            let maksymalna_rata = mapa_rat.values().map(|r| r.wartosc()).max().unwrap_or_default();
+
+            if self.budzet.is_some_and(|budzet| maksymalna_rata > budzet) {
+                przekroczenia += 1;
+            }
+
+            odsetki.push(mapa_rat.values().map(|r| r.odsetki).sum());
+            maksymalne_raty.push(maksymalna_rata);
+        }
+
+        Ok(WynikSymulacji {
+            nazwa: kredyt.nazwa,
+            liczba_sciezek: self.liczba_sciezek,
+            odsetki: Percentyle::new(odsetki),
+            maksymalna_rata: Percentyle::new(maksymalne_raty),
+            przekroczenie_budzetu: self.budzet.map(|_| przekroczenia as f64 / self.liczba_sciezek.max(1) as f64),
+        })
+    }
+}
+
+impl fmt::Display for WynikSymulacji {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Symulacja oprocentowania oferty `{}` ({} sciezek):", self.nazwa, self.liczba_sciezek)?;
+        writeln!(f, "    * Odsetki: {}", self.odsetki)?;
+        writeln!(f, "    * Maksymalna rata: {}", self.maksymalna_rata)?;
+
+        if let Some(prawdopodobienstwo) = self.przekroczenie_budzetu {
+            writeln!(f, "    * Prawdopodobienstwo przekroczenia budzetu: {:.1}%", prawdopodobienstwo * 100.0)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StrukturaSplaty, ZmianaMarzy};
+
+    fn symulacja(zmiennosc: f64) -> Symulacja {
+        Symulacja {
+            oferta: "data/kredyt/pekao_sa_25.json".to_string(),
+            model: ModelStopy::BladzenieLosowe { zmiennosc },
+            liczba_sciezek: 50,
+            ziarno: 7,
+            okres_zmiany: 3,
+            minimalne_oprocentowanie: 0.0,
+            budzet: None,
+        }
+    }
+
+    #[test]
+    fn stala_sciezka_daje_harmonogram_oferty() {
+        let mut kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        kredyt.zmiany_marzy.push(ZmianaMarzy { od: 0, to: Some(12), zmiana: -1.0 });
+        kredyt.struktura_splaty = StrukturaSplaty::TylkoOdsetki { miesiecy: Some(24) };
+        let raty = |mapa_rat: BTreeMap<u64, Rata>| mapa_rat.into_iter().map(|(n, r)| (n, r.kapital, r.odsetki)).collect::<Vec<_>>();
+        let oferta = KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        assert_eq!(raty(symulacja(0.0).harmonogram(&kredyt, &mut rng)), raty(oferta));
+    }
+
+    #[test]
+    fn wynik_powtarzalny_dla_ziarna() {
+        let pierwszy = symulacja(1.0).oblicz().unwrap();
+        let drugi = symulacja(1.0).oblicz().unwrap();
+
+        assert_eq!(pierwszy.odsetki.p50, drugi.odsetki.p50);
+        assert!(pierwszy.odsetki.p5 <= pierwszy.odsetki.p50 && pierwszy.odsetki.p50 <= pierwszy.odsetki.p95);
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, KalkulatorRaty, Kwota, Nadplata};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum CelSplaty {
+    /// Kredyt ma zostac splacony w najwyzej `liczba_rat` ratach.
+    Koniec { liczba_rat: u64 },
+    /// Suma odsetek nie moze przekroczyc `maksymalnie`.
+    Odsetki { maksymalnie: Kwota },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "typ", rename_all = "snake_case")]
+pub enum SzukanaNadplata {
+    /// Stala miesieczna nadplata od raty `od` do konca kredytu.
+    Miesieczna {
+        #[serde(default)]
+        od: u64,
+    },
+    /// Jednorazowa nadplata w racie `w_racie`.
+    Jednorazowa { w_racie: u64 },
+}
+
+impl SzukanaNadplata {
+    fn nadplata(&self, wartosc: Kwota, okres_kredytowania: u64) -> Nadplata {
+        match *self {
+            SzukanaNadplata::Miesieczna { od } => Nadplata { wartosc, from: od, to: okres_kredytowania, po_okresie: false },
+            SzukanaNadplata::Jednorazowa { w_racie } => Nadplata { wartosc, from: w_racie, to: w_racie + 1, po_okresie: false },
+        }
+    }
+}
+
+/// Szuka najmniejszej nadplaty (dodawanej do nadplat oferty), przy ktorej osiagniety jest cel.
+#[derive(Serialize, Deserialize)]
+pub struct Szukanie {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    #[serde(rename = "cel")]
+    pub cel: CelSplaty,
+
+    #[serde(rename = "nadplata")]
+    pub nadplata: SzukanaNadplata,
+}
+
+pub struct WynikSzukania {
+    pub nazwa: String,
+    pub nadplata: Option<Nadplata>,
+    pub liczba_rat: u64,
+    pub odsetki: Kwota,
+}
+
+impl Szukanie {
+    fn oblicz_raty(&self, kredyt: &Kredyt, wartosc: Kwota) -> (u64, Kwota) {
+        let mut nadplaty = kredyt.wszystkie_nadplaty();
+        nadplaty.dodaj(self.nadplata.nadplata(wartosc, kredyt.okres_kredytowania));
+
+        let mapa_rat = KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), kredyt)
+            .nadplaty(nadplaty)
+            .mapa_rat();
+
+        (mapa_rat.len() as u64, mapa_rat.values().map(|r| r.odsetki).sum())
+    }
+
+    fn osiagniety(&self, (liczba_rat, odsetki): (u64, Kwota)) -> bool {
+        match self.cel {
+            CelSplaty::Koniec { liczba_rat: cel } => liczba_rat <= cel,
+            CelSplaty::Odsetki { maksymalnie } => odsetki <= maksymalnie,
+        }
+    }
+
+    /// Bisekcja po kwocie nadplaty - wieksza nadplata nigdy nie wydluza kredytu ani nie zwieksza odsetek.
+    pub fn oblicz(&self) -> Result<WynikSzukania, Blad> {
+        let kredyt = Kredyt::wczytaj(&self.oferta)?;
+        let mut dolna = Kwota::ZERO;
+        let mut gorna = kredyt.kwota_kredytowania();
+
+        let nadplata = if self.osiagniety(self.oblicz_raty(&kredyt, dolna)) {
+            Some(Kwota::ZERO)
+        } else if !self.osiagniety(self.oblicz_raty(&kredyt, gorna)) {
+            None
+        } else {
+            while gorna.grosze() - dolna.grosze() > 1 {
+                let srodek = Kwota::z_groszy((dolna.grosze() + gorna.grosze()) / 2);
+
+                if self.osiagniety(self.oblicz_raty(&kredyt, srodek)) {
+                    gorna = srodek;
+                } else {
+                    dolna = srodek;
+                }
+            }
+
+            Some(gorna)
+        };
+
+        let (liczba_rat, odsetki) = self.oblicz_raty(&kredyt, nadplata.unwrap_or_default());
+
+        Ok(WynikSzukania {
+            nadplata: nadplata.map(|wartosc| self.nadplata.nadplata(wartosc, kredyt.okres_kredytowania)),
+            nazwa: kredyt.nazwa,
+            liczba_rat,
+            odsetki,
+        })
+    }
+}
+
+impl fmt::Display for WynikSzukania {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Szukanie nadplaty dla oferty `{}`:", self.nazwa)?;
+
+        match &self.nadplata {
+            Some(nadplata) => {
+                writeln!(f, "    * Nadplata: {:.2}zl w okresie {} - {}", nadplata.wartosc, nadplata.from, nadplata.to)?;
+                writeln!(f, "    * Liczba rat: {}, odsetki: {:.2}zl", self.liczba_rat, self.odsetki)
+            },
+            None => writeln!(f, "    * Cel nieosiagalny dla zadanego typu nadplaty"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cel_osiagniety_przez_splate_z_oferty_nie_wymaga_nadplaty() {
+        let kredyt = Kredyt::wczytaj("data/kredyt/pekao_sa_25.json").unwrap();
+        let oferta = KalkulatorRaty::z_kredytu(kredyt.kwota_kredytowania(), &kredyt).mapa_rat();
+        let liczba_rat = oferta.len() as u64;
+        let szukanie = |liczba_rat: u64| Szukanie {
+            oferta: "data/kredyt/pekao_sa_25.json".to_string(),
+            cel: CelSplaty::Koniec { liczba_rat },
+            nadplata: SzukanaNadplata::Miesieczna { od: 0 },
+        }.oblicz().unwrap();
+
+        let wynik = szukanie(liczba_rat);
+        assert_eq!(wynik.nadplata.map(|n| n.wartosc), Some(Kwota::ZERO));
+        assert_eq!(wynik.odsetki, oferta.values().map(|r| r.odsetki).sum());
+
+        let wynik = szukanie(liczba_rat - 12);
+        assert!(wynik.liczba_rat <= liczba_rat - 12);
+        assert!(wynik.nadplata.unwrap().wartosc > Kwota::ZERO);
+    }
+}
@@ -0,0 +1,143 @@
+//! Tryby programu: kazdy wczytuje plik z danymi i wypisuje wynik obliczen.
+
+use hipoteka::{
+    wczytaj_json, Blad, Gospodarstwo, Kalkulator, Koszty, Kredyt, Kwota, MaksymalnyKredyt,
+    NadplataCzyInwestycja, OprocentowanieZRaty, Optymalizacja, PorownanieNajmu, PorownanieRealne, Siatka,
+    Sprzedaz, Symulacja, Szukanie, TestStresu,
+};
+
+pub fn oferta(path: &str) -> Result<(), Blad> {
+    let kredyt = Kredyt::wczytaj(path)?;
+    let rata_banku = OprocentowanieZRaty::oblicz(&kredyt);
+    let kalkulator = Kalkulator::new(kredyt)?;
+    let warunki = kalkulator.koszty_warunkow()?;
+
+    println!("{}", kalkulator);
+
+    if !warunki.is_empty() {
+        println!("Warunki marzy i produkty:");
+
+        for warunek in warunki {
+            println!("{}", warunek);
+        }
+
+        println!();
+    }
+
+    // bledna rata banku nie przekresla raportu oferty
+    match rata_banku {
+        Ok(Some(rata_banku)) => println!("{}", rata_banku),
+        Ok(None) => {},
+        Err(blad) => println!("UWAGA: {}\n", blad),
+    }
+
+    Ok(())
+}
+
+pub fn refinansowanie(path: &str) -> Result<(), Blad> {
+    let kredyt = Kredyt::wczytaj(path)?;
+    let mut bez_refinansowania = kredyt.clone();
+    bez_refinansowania.refinansowanie = None;
+
+    let pozostanie = Kalkulator::new(bez_refinansowania)?;
+    let przeniesienie = Kalkulator::new(kredyt)?;
+    let koszt_pozostania = pozostanie.koszt_kredytu(None);
+    let koszt_przeniesienia = przeniesienie.koszt_kredytu(None);
+
+    println!("{}", przeniesienie);
+    println!("Koszt kredytu bez refinansowania: {:.2}zl", koszt_pozostania);
+    println!("Koszt kredytu z refinansowaniem: {:.2}zl", koszt_przeniesienia);
+    println!("Oszczednosc z refinansowania: {:.2}zl", koszt_pozostania - koszt_przeniesienia);
+    println!();
+    Ok(())
+}
+
+pub fn koszty(path: &str) -> Result<(), Blad> {
+    let koszty_mieszkania = Koszty::wczytaj(path)?;
+    let koszty = koszty_mieszkania.oblicz();
+    let total: Kwota = koszty.values().map(|v| v.total()).sum();
+
+    println!("Koszt utrzymania `{}` wynosi: {} zl", koszty_mieszkania.nazwa(), total);
+
+    for (_, koszt) in koszty.iter() {
+        println!("    * {}", koszt);
+    }
+
+    println!();
+    Ok(())
+}
+
+pub fn zdolnosc(path: &str) -> Result<(), Blad> {
+    println!("{}", Gospodarstwo::wczytaj(path)?.oblicz()?);
+    Ok(())
+}
+
+pub fn siatka(path: &str) -> Result<(), Blad> {
+    let siatka: Siatka = wczytaj_json(path)?;
+
+    println!("{}", siatka.oblicz()?);
+    Ok(())
+}
+
+pub fn stres(path: &str) -> Result<(), Blad> {
+    let test: TestStresu = wczytaj_json(path)?;
+
+    println!("{}", test.oblicz()?);
+    Ok(())
+}
+
+pub fn symulacja(path: &str) -> Result<(), Blad> {
+    let symulacja: Symulacja = wczytaj_json(path)?;
+
+    println!("{}", symulacja.oblicz()?);
+    Ok(())
+}
+
+pub fn optymalizacja(path: &str) -> Result<(), Blad> {
+    let optymalizacja: Optymalizacja = wczytaj_json(path)?;
+
+    println!("{}", optymalizacja.oblicz()?);
+    Ok(())
+}
+
+pub fn szukanie(path: &str) -> Result<(), Blad> {
+    let szukanie: Szukanie = wczytaj_json(path)?;
+
+    println!("{}", szukanie.oblicz()?);
+    Ok(())
+}
+
+pub fn maksymalny_kredyt(path: &str) -> Result<(), Blad> {
+    let maksymalny_kredyt: MaksymalnyKredyt = wczytaj_json(path)?;
+
+    println!("{}", maksymalny_kredyt.oblicz()?);
+    Ok(())
+}
+
+pub fn wynajem(path: &str) -> Result<(), Blad> {
+    let porownanie: PorownanieNajmu = wczytaj_json(path)?;
+
+    println!("{}", porownanie.oblicz()?);
+    Ok(())
+}
+
+pub fn inflacja(path: &str) -> Result<(), Blad> {
+    let porownanie: PorownanieRealne = wczytaj_json(path)?;
+
+    println!("{}", porownanie.oblicz()?);
+    Ok(())
+}
+
+pub fn inwestycja(path: &str) -> Result<(), Blad> {
+    let porownanie: NadplataCzyInwestycja = wczytaj_json(path)?;
+
+    println!("{}", porownanie.oblicz()?);
+    Ok(())
+}
+
+pub fn sprzedaz(path: &str) -> Result<(), Blad> {
+    let sprzedaz: Sprzedaz = wczytaj_json(path)?;
+
+    println!("{}", sprzedaz.oblicz()?);
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{wczytaj_json, Blad, Kwota, Rata};
+
+/// Kredyt w walucie obcej: kwota kredytu jest przeliczana na walute po kursie kupna banku z dnia
+/// uruchomienia, a raty - na zlote po kursie sprzedazy z miesiaca splaty. Oprocentowanie i nadplaty
+/// oferty sa podawane w walucie kredytu, wartosc nieruchomosci i koszty - w zlotych.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Waluta {
+    #[serde(rename = "kod")]
+    pub kod: String,
+
+    /// Sciezka do pliku `TabelaKursow`, wzgledem pliku oferty.
+    #[serde(rename = "kursy")]
+    pub kursy: String,
+
+    /// Kursy z pliku `kursy`, wczytywane i sprawdzane razem z oferta.
+    #[serde(skip)]
+    pub tabela: Vec<f64>,
+
+    /// Roznica miedzy kursem sprzedazy i kupna banku, w procentach kursu sredniego.
+    #[serde(rename = "spread", default)]
+    pub spread: f64,
+}
+
+/// Srednie kursy waluty w kolejnych miesiacach kredytu, od miesiaca uruchomienia.
+/// Po ostatnim miesiacu tabeli obowiazuje ostatni kurs.
+#[derive(Serialize, Deserialize)]
+pub struct TabelaKursow {
+    #[serde(rename = "kod")]
+    pub kod: String,
+
+    #[serde(rename = "kursy")]
+    pub kursy: Vec<f64>,
+}
+
+impl TabelaKursow {
+    pub fn wczytaj(path: &str) -> Result<Self, Blad> {
+        wczytaj_json(path)
+    }
+}
+
+impl Waluta {
+    /// Wczytuje tabele kursow do `tabela`, sprawdzajac, czy jest niepusta i dotyczy waluty kredytu.
+    pub fn wczytaj_kursy(&mut self) -> Result<(), Blad> {
+        let tabela = TabelaKursow::wczytaj(&self.kursy)?;
+
+        if tabela.kod != self.kod {
+            return Err(Blad::Dane(format!("tabela kursow `{}` ({}) nie pasuje do waluty kredytu {}", self.kursy, tabela.kod, self.kod)));
+        }
+
+        if tabela.kursy.is_empty() {
+            return Err(Blad::Dane(format!("tabela kursow `{}` jest pusta", self.kursy)));
+        }
+
+        self.tabela = tabela.kursy;
+        Ok(())
+    }
+}
+
+pub struct KursyWaluty {
+    kod: String,
+    kursy: Vec<f64>,
+    spread: f64,
+}
+
+impl KursyWaluty {
+    pub fn new(waluta: &Waluta) -> Result<Self, Blad> {
+        if waluta.tabela.is_empty() {
+            return Err(Blad::Dane(format!("brak kursow waluty {} - tabela `{}` nie zostala wczytana", waluta.kod, waluta.kursy)));
+        }
+
+        Ok(Self { kod: waluta.kod.clone(), kursy: waluta.tabela.clone(), spread: waluta.spread })
+    }
+
+    pub fn kod(&self) -> &str {
+        &self.kod
+    }
+
+    pub fn kurs(&self, miesiac: u64) -> f64 {
+        self.kursy.get(miesiac as usize).or_else(|| self.kursy.last()).copied().unwrap()
+    }
+
+    pub fn kupno(&self, miesiac: u64) -> f64 {
+        self.kurs(miesiac) * (1.0 - self.spread / 200.0)
+    }
+
+    pub fn sprzedaz(&self, miesiac: u64) -> f64 {
+        self.kurs(miesiac) * (1.0 + self.spread / 200.0)
+    }
+
+    /// Kwota kredytu w walucie, po kursie kupna z dnia uruchomienia.
+    pub fn w_walucie(&self, kwota_kredytowania: Kwota) -> Kwota {
+        kwota_kredytowania * (1.0 / self.kupno(0))
+    }
+
+    /// Przelicza harmonogram w walucie na zlote. Kapital i nadplaty sa wyceniane proporcjonalnie
+    /// do kwoty kredytu w zlotych (sumuja sie do niej co do grosza), a roznica wzgledem wartosci
+    /// po kursie sprzedazy trafia do `roznice_kursowe`.
+    pub fn w_zlotych(&self, kwota_kredytowania: Kwota, mapa_rat: &BTreeMap<u64, Rata>) -> BTreeMap<u64, Rata> {
+        let kwota_w_walucie = self.w_walucie(kwota_kredytowania);
+        let mut splacono_w_walucie = Kwota::ZERO;
+        let mut splacono = Kwota::ZERO;
+
+        mapa_rat.iter()
+            .map(|(&numer_raty, rata)| {
+                let sprzedaz = self.sprzedaz(numer_raty);
+                splacono_w_walucie += rata.kapital();
+
+                let splacono_do = kwota_kredytowania * (splacono_w_walucie.zl() / kwota_w_walucie.zl());
+                let kapital = splacono_do - splacono;
+                let nadplata = if rata.kapital() > Kwota::ZERO {
+                    kapital * (rata.nadplata.zl() / rata.kapital().zl())
+                } else {
+                    Kwota::ZERO
+                };
+                splacono = splacono_do;
+
+                let rata_w_zlotych = Rata {
+                    kapital: kapital - nadplata,
+                    odsetki: rata.odsetki * sprzedaz,
+                    nadplata,
+                    roznice_kursowe: rata.kapital * sprzedaz - (kapital - nadplata),
+                    roznice_kursowe_nadplaty: rata.nadplata * sprzedaz - nadplata,
+                    doplata: Kwota::ZERO,
+                };
+
+                (numer_raty, rata_w_zlotych)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Kredyt;
+
+    fn waluta(tabela: Vec<f64>) -> Waluta {
+        Waluta { kod: "CHF".to_string(), kursy: String::new(), spread: 0.0, tabela }
+    }
+
+    #[test]
+    fn roznice_kursowe_nadplaty_poza_rata() {
+        let kursy = KursyWaluty::new(&waluta(vec![4.0, 5.0])).unwrap();
+        let mut mapa_rat = BTreeMap::new();
+        mapa_rat.insert(1, Rata { kapital: Kwota::from(100.0), odsetki: Kwota::from(10.0), nadplata: Kwota::from(50.0), ..Rata::default() });
+        mapa_rat.insert(2, Rata { kapital: Kwota::from(850.0), ..Rata::default() });
+
+        let w_zlotych = kursy.w_zlotych(Kwota::from(4_000.0), &mapa_rat);
+        let rata = w_zlotych[&1];
+
+        // kapital ksiegowy po kursie z dnia uruchomienia, nadplata 50 CHF po 5 zl kosztuje 250 zl
+        assert_eq!((rata.kapital, rata.nadplata), (Kwota::from(400.0), Kwota::from(200.0)));
+        assert_eq!(rata.roznice_kursowe, Kwota::from(100.0));
+        assert_eq!(rata.roznice_kursowe_nadplaty, Kwota::from(50.0));
+        assert_eq!(rata.wartosc(), Kwota::from(400.0 + 50.0 + 100.0));
+        assert_eq!(rata.wplata(), rata.wartosc() + Kwota::from(250.0));
+    }
+
+    #[test]
+    fn tabela_innej_waluty_jest_bledem_przy_wczytaniu() {
+        let katalog = std::env::temp_dir().join(format!("hipoteka_kursy_{}", std::process::id()));
+        std::fs::create_dir_all(&katalog).unwrap();
+        std::fs::write(katalog.join("eur.json"), r#"{"kod": "EUR", "kursy": [4.5]}"#).unwrap();
+        std::fs::write(katalog.join("oferta.json"), format!(
+            r#"{{"rozszerza": "{}", "waluta": {{"kod": "CHF", "kursy": "eur.json"}}}}"#,
+            std::fs::canonicalize("data/kredyt/mbank.json").unwrap().display()
+        )).unwrap();
+
+        let wynik = Kredyt::wczytaj(katalog.join("oferta.json").to_str().unwrap());
+        std::fs::remove_dir_all(&katalog).unwrap();
+
+        assert!(matches!(wynik, Err(Blad::Dane(_))));
+        assert!(KursyWaluty::new(&waluta(Vec::new())).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{wczytaj_json, Blad, Kwota};
+
+/// Zmiana wartosci nieruchomosci w czasie: roczne zmiany procentowe z `zmiany_roczne` (albo z pliku
+/// indeksu), a dla lat poza nimi stala zmiana `roczna`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ZmianaWartosci {
+    #[serde(rename = "roczna", default)]
+    pub roczna: f64,
+
+    /// Sciezka do pliku `Indeks`, wzgledem pliku oferty. Indeks jest wczytywany razem z oferta
+    /// do `zmiany_roczne`.
+    #[serde(rename = "indeks", default)]
+    pub indeks: Option<String>,
+
+    #[serde(rename = "zmiany_roczne", default)]
+    pub zmiany_roczne: Vec<f64>,
+}
+
+/// Roczne zmiany cen nieruchomosci w procentach, od roku zakupu.
+#[derive(Serialize, Deserialize)]
+pub struct Indeks {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    #[serde(rename = "zmiany_roczne")]
+    pub zmiany_roczne: Vec<f64>,
+}
+
+impl Indeks {
+    pub fn wczytaj(path: &str) -> Result<Self, Blad> {
+        wczytaj_json(path)
+    }
+}
+
+pub struct WartoscNieruchomosci {
+    poczatkowa: Kwota,
+    zmiany_roczne: Vec<f64>,
+    zmiana_roczna: f64,
+}
+
+impl WartoscNieruchomosci {
+    pub fn new(poczatkowa: Kwota, zmiana: &ZmianaWartosci) -> Self {
+        Self { poczatkowa, zmiany_roczne: zmiana.zmiany_roczne.clone(), zmiana_roczna: zmiana.roczna }
+    }
+
+    fn zmiana(&self, rok: usize) -> f64 {
+        self.zmiany_roczne.get(rok).copied().unwrap_or(self.zmiana_roczna) / 100.0
+    }
+
+    /// Wartosc po `miesiac` miesiacach, zmiana w trakcie roku rozkladana jest rownomiernie (procent skladany).
+    pub fn w_miesiacu(&self, miesiac: u64) -> Kwota {
+        let pelne_lata = (miesiac / 12) as usize;
+        let wspolczynnik = (0..pelne_lata).map(|rok| 1.0 + self.zmiana(rok)).product::<f64>()
+            * (1.0 + self.zmiana(pelne_lata)).powf((miesiac % 12) as f64 / 12.0);
+
+        self.poczatkowa * wspolczynnik
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wartosc_rosnie_wg_indeksu_a_potem_stalej_zmiany() {
+        let zmiana = ZmianaWartosci { roczna: 2.0, indeks: None, zmiany_roczne: vec![10.0, -10.0] };
+        let wartosc = WartoscNieruchomosci::new(Kwota::from(100_000.0), &zmiana);
+
+        assert_eq!(wartosc.w_miesiacu(12), Kwota::from(110_000.0));
+        assert_eq!(wartosc.w_miesiacu(24), Kwota::from(99_000.0));
+        assert_eq!(wartosc.w_miesiacu(36), Kwota::from(100_980.0));
+    }
+}
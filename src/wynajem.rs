@@ -0,0 +1,150 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{Blad, kredyt::Kredyt, kalkulator::Kalkulator, Koszty, Kwota};
+
+/// Porownanie zakupu na kredyt z wynajmem. Obie sciezki maja ten sam miesieczny budzet -
+/// sciezka z nizszymi wydatkami inwestuje roznice, a najemca inwestuje od poczatku gotowke,
+/// ktora kupujacy przeznacza na wklad wlasny i koszty jednorazowe.
+#[derive(Serialize, Deserialize)]
+pub struct PorownanieNajmu {
+    #[serde(rename = "oferta")]
+    pub oferta: String,
+
+    /// Sciezki do plikow `Koszty` z kosztami utrzymania wlasnego mieszkania.
+    #[serde(rename = "koszty_utrzymania", default)]
+    pub koszty_utrzymania: Vec<String>,
+
+    #[serde(rename = "czynsz")]
+    pub czynsz: Kwota,
+
+    /// Roczna indeksacja czynszu w procentach.
+    #[serde(rename = "indeksacja_czynszu", default)]
+    pub indeksacja_czynszu: f64,
+
+    /// Roczna stopa zwrotu z inwestowanych oszczednosci w procentach.
+    #[serde(rename = "zwrot_z_inwestycji", default)]
+    pub zwrot_z_inwestycji: f64,
+
+    /// Horyzont porownania w latach.
+    #[serde(rename = "horyzont")]
+    pub horyzont: u64,
+}
+
+pub struct RokPorownania {
+    pub rok: u64,
+    pub majatek_kupno: Kwota,
+    pub majatek_najem: Kwota,
+}
+
+pub struct WynikPorownaniaNajmu {
+    pub nazwa: String,
+    pub lata: Vec<RokPorownania>,
+}
+
+impl PorownanieNajmu {
+    pub fn oblicz(&self) -> Result<WynikPorownaniaNajmu, Blad> {
+        let kalkulator = Kalkulator::new(Kredyt::wczytaj(&self.oferta)?)?;
+        let utrzymanie: Kwota = self.koszty_utrzymania.iter().map(|k| Ok(Koszty::wczytaj(k)?.miesiecznie())).sum::<Result<_, Blad>>()?;
+        let zwrot = (1.0 + self.zwrot_z_inwestycji / 100.0).powf(1.0 / 12.0);
+        let koszty = kalkulator.mapa_kosztow();
+        let koszty_do = |numer_raty: u64| koszty.values().map(|k| k.do_raty(numer_raty)).sum::<Kwota>();
+
+        let gotowka = kalkulator.wartosc_hipoteki() - kalkulator.kwota_kredytowania() + koszty_do(0);
+        let mut kapital_do_splaty = kalkulator.kwota_kredytowania();
+        let mut portfel_kupno = Kwota::ZERO;
+        let mut portfel_najem = gotowka;
+        let mut czynsz = self.czynsz;
+        let mut lata = Vec::new();
+
+        for miesiac in 0..self.horyzont * 12 {
+            if miesiac > 0 && miesiac % 12 == 0 {
+                czynsz = czynsz * (1.0 + self.indeksacja_czynszu / 100.0);
+            }
+
+            let rata = kalkulator.mapa_rat().get(&miesiac).copied().unwrap_or_default();
+            let wydatki_kupno = rata.wplata() + koszty_do(miesiac + 1) - koszty_do(miesiac) + utrzymanie;
+            let budzet = wydatki_kupno.max(czynsz);
+
+            kapital_do_splaty -= rata.kapital();
+            portfel_kupno = portfel_kupno * zwrot + (budzet - wydatki_kupno);
+            portfel_najem = portfel_najem * zwrot + (budzet - czynsz);
+
+            if (miesiac + 1) % 12 == 0 {
+                lata.push(RokPorownania {
+                    rok: (miesiac + 1) / 12,
+                    majatek_kupno: kalkulator.wartosc_nieruchomosci(miesiac + 1) - kapital_do_splaty + portfel_kupno,
+                    majatek_najem: portfel_najem,
+                });
+            }
+        }
+
+        Ok(WynikPorownaniaNajmu { nazwa: kalkulator.nazwa().to_string(), lata })
+    }
+}
+
+impl WynikPorownaniaNajmu {
+    /// Pierwszy rok, od ktorego majatek przy zakupie nie jest nizszy niz przy wynajmie.
+    pub fn rok_zrownania(&self) -> Option<u64> {
+        let mut rok_zrownania = None;
+
+        for rok in self.lata.iter() {
+            match (rok.majatek_kupno >= rok.majatek_najem, rok_zrownania) {
+                (true, None) => rok_zrownania = Some(rok.rok),
+                (false, Some(_)) => rok_zrownania = None,
+                _ => {},
+            }
+        }
+
+        rok_zrownania
+    }
+}
+
+impl fmt::Display for WynikPorownaniaNajmu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Zakup (`{}`) czy wynajem:", self.nazwa)?;
+
+        for rok in self.lata.iter() {
+            writeln!(f, "    * Rok {}: majatek przy zakupie {:.2}zl, przy wynajmie {:.2}zl", rok.rok, rok.majatek_kupno, rok.majatek_najem)?;
+        }
+
+        match self.rok_zrownania() {
+            Some(rok) => writeln!(f, "Zakup oplaca sie od {}. roku", rok),
+            None => writeln!(f, "Zakup nie oplaca sie w horyzoncie porownania"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn porownanie(czynsz: f64) -> WynikPorownaniaNajmu {
+        PorownanieNajmu {
+            oferta: "data/kredyt/pekao_sa_25.json".to_string(),
+            koszty_utrzymania: Vec::new(),
+            czynsz: Kwota::from(czynsz),
+            indeksacja_czynszu: 0.0,
+            zwrot_z_inwestycji: 0.0,
+            horyzont: 5,
+        }.oblicz().unwrap()
+    }
+
+    #[test]
+    fn zakup_oplaca_sie_tylko_przy_wysokim_czynszu() {
+        assert_eq!(porownanie(0.0).rok_zrownania(), None);
+        assert_eq!(porownanie(50_000.0).rok_zrownania(), Some(1));
+    }
+
+    #[test]
+    fn rok_zrownania_liczony_od_ostatniego_wyprzedzenia() {
+        let rok = |rok: u64, kupno: f64| RokPorownania { rok, majatek_kupno: Kwota::from(kupno), majatek_najem: Kwota::from(100.0) };
+        let wynik = WynikPorownaniaNajmu {
+            nazwa: String::new(),
+            lata: vec![rok(1, 50.0), rok(2, 150.0), rok(3, 50.0), rok(4, 100.0), rok(5, 200.0)],
+        };
+
+        assert_eq!(wynik.rok_zrownania(), Some(4));
+    }
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{wczytaj_json, Blad, Koszty, Kwota, Rata};
+
+/// Pozycja miesiecznego budzetu gospodarstwa domowego (dochod netto albo rata zobowiazania).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pozycja {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    #[serde(rename = "wartosc")]
+    pub wartosc: Kwota,
+}
+
+/// Parametry oceny zdolnosci kredytowej wg Rekomendacji S KNF.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Gospodarstwo {
+    #[serde(rename = "nazwa")]
+    pub nazwa: String,
+
+    #[serde(rename = "dochody")]
+    pub dochody: Vec<Pozycja>,
+
+    /// Sciezki do plikow `Koszty` z kosztami utrzymania.
+    #[serde(rename = "koszty_utrzymania", default)]
+    pub koszty_utrzymania: Vec<String>,
+
+    /// Miesieczne koszty utrzymania z plikow `koszty_utrzymania`, wczytywane raz w `wczytaj`.
+    #[serde(skip)]
+    pub utrzymanie: Kwota,
+
+    #[serde(rename = "zobowiazania", default)]
+    pub zobowiazania: Vec<Pozycja>,
+
+    /// Maksymalny udzial rat wszystkich zobowiazan w dochodzie, w procentach.
+    #[serde(rename = "dsti", default = "Gospodarstwo::domyslne_dsti")]
+    pub dsti: f64,
+
+    /// Bufor doliczany do oprocentowania przy badaniu zdolnosci, w punktach procentowych.
+    #[serde(rename = "bufor", default = "Gospodarstwo::domyslny_bufor")]
+    pub bufor: f64,
+
+    #[serde(rename = "oprocentowanie")]
+    pub oprocentowanie: f64,
+
+    #[serde(rename = "okres_kredytowania")]
+    pub okres_kredytowania: u64,
+
+    /// Najdluzszy okres, jaki bank przyjmuje do badania zdolnosci (Rekomendacja S: 25 lat).
+    #[serde(rename = "maksymalny_okres", default = "Gospodarstwo::domyslny_maksymalny_okres")]
+    pub maksymalny_okres: u64,
+
+    #[serde(rename = "wklad_wlasny", default)]
+    pub wklad_wlasny: Option<f64>,
+}
+
+impl Gospodarstwo {
+    pub fn wczytaj(path: &str) -> Result<Self, Blad> {
+        let mut gospodarstwo: Self = wczytaj_json(path)?;
+        gospodarstwo.utrzymanie = gospodarstwo.koszty_utrzymania.iter()
+            .map(|k| Ok(Koszty::wczytaj(k)?.miesiecznie()))
+            .sum::<Result<_, Blad>>()?;
+
+        Ok(gospodarstwo)
+    }
+
+    fn domyslne_dsti() -> f64 { 40.0 }
+
+    fn domyslny_bufor() -> f64 { 2.5 }
+
+    fn domyslny_maksymalny_okres() -> u64 { 300 }
+
+    pub fn dochod(&self) -> Kwota {
+        self.dochody.iter().map(|d| d.wartosc).sum()
+    }
+
+    pub fn zobowiazania(&self) -> Kwota {
+        self.zobowiazania.iter().map(|z| z.wartosc).sum()
+    }
+
+    /// Dochod pozostajacy po kosztach utrzymania i ratach obecnych zobowiazan.
+    pub fn nadwyzka(&self) -> Kwota {
+        self.dochod() - self.utrzymanie - self.zobowiazania()
+    }
+
+    pub fn oblicz(&self) -> Result<ZdolnoscKredytowa, Blad> {
+        if let Some(wklad) = self.wklad_wlasny.filter(|wklad| !(0.0..100.0).contains(wklad)) {
+            return Err(Blad::Dane(format!("wklad wlasny gospodarstwa `{}` musi byc z przedzialu [0, 100)%, podano {}%", self.nazwa, wklad)));
+        }
+
+        let dochod = self.dochod();
+        let zobowiazania = self.zobowiazania();
+        let limit_dsti = dochod * (self.dsti / 100.0) - zobowiazania;
+        let dostepna_rata = limit_dsti.min(self.nadwyzka()).max(Kwota::ZERO);
+        let oprocentowanie_testowe = self.oprocentowanie + self.bufor;
+        let okres = self.okres_kredytowania.min(self.maksymalny_okres);
+        let maksymalna_kwota = Rata::kwota_kredytu(dostepna_rata, oprocentowanie_testowe, okres);
+        let maksymalna_wartosc = self.wklad_wlasny
+            .map(|wklad| maksymalna_kwota * (1.0 / (1.0 - wklad / 100.0)));
+
+        Ok(ZdolnoscKredytowa {
+            nazwa: self.nazwa.clone(),
+            dochod,
+            koszty_utrzymania: self.utrzymanie,
+            zobowiazania,
+            dostepna_rata,
+            oprocentowanie_testowe,
+            okres,
+            maksymalna_kwota,
+            maksymalna_wartosc,
+        })
+    }
+}
+
+pub struct ZdolnoscKredytowa {
+    pub nazwa: String,
+    pub dochod: Kwota,
+    pub koszty_utrzymania: Kwota,
+    pub zobowiazania: Kwota,
+    /// Najwyzsza rata nowego kredytu mieszczaca sie w DSTI i nadwyzce budzetu.
+    pub dostepna_rata: Kwota,
+    pub oprocentowanie_testowe: f64,
+    pub okres: u64,
+    pub maksymalna_kwota: Kwota,
+    pub maksymalna_wartosc: Option<Kwota>,
+}
+
+impl fmt::Display for ZdolnoscKredytowa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Zdolnosc kredytowa `{}`:", self.nazwa)?;
+        writeln!(f, "    * Dochod netto: {:.2}zl", self.dochod)?;
+        writeln!(f, "    * Koszty utrzymania: {:.2}zl", self.koszty_utrzymania)?;
+        writeln!(f, "    * Obecne zobowiazania: {:.2}zl", self.zobowiazania)?;
+        writeln!(f, "    * Dostepna rata: {:.2}zl", self.dostepna_rata)?;
+        writeln!(f, "    * Oprocentowanie z buforem: {:.2}%, okres: {} mies.", self.oprocentowanie_testowe, self.okres)?;
+        writeln!(f, "Maksymalna kwota kredytu: {:.2}zl", self.maksymalna_kwota)?;
+
+        if let Some(wartosc) = self.maksymalna_wartosc {
+            writeln!(f, "Maksymalna wartosc nieruchomosci: {:.2}zl", wartosc)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Zaokraglenie;
+
+    fn gospodarstwo(wklad_wlasny: Option<f64>) -> Gospodarstwo {
+        Gospodarstwo {
+            nazwa: "test".to_string(),
+            dochody: vec![Pozycja { nazwa: "pensja".to_string(), wartosc: Kwota::from(10_000.0) }],
+            koszty_utrzymania: Vec::new(),
+            utrzymanie: Kwota::from(3_000.0),
+            zobowiazania: vec![Pozycja { nazwa: "leasing".to_string(), wartosc: Kwota::from(1_000.0) }],
+            dsti: 40.0,
+            bufor: 2.5,
+            oprocentowanie: 7.0,
+            okres_kredytowania: 360,
+            maksymalny_okres: 300,
+            wklad_wlasny,
+        }
+    }
+
+    #[test]
+    fn rata_ograniczona_przez_dsti_i_nadwyzke() {
+        let zdolnosc = gospodarstwo(Some(20.0)).oblicz().unwrap();
+        let rata = Rata::new(zdolnosc.maksymalna_kwota, zdolnosc.oprocentowanie_testowe, zdolnosc.okres, Zaokraglenie::default());
+
+        // 40% z 10 000 zl minus 1 000 zl leasingu, nadwyzka budzetu (6 000 zl) jest wyzsza
+        assert_eq!(zdolnosc.dostepna_rata, Kwota::from(3_000.0));
+        assert_eq!(zdolnosc.okres, 300);
+        assert!((rata.wartosc() - zdolnosc.dostepna_rata).grosze().abs() <= 1);
+        assert_eq!(zdolnosc.maksymalna_wartosc, Some(zdolnosc.maksymalna_kwota * (1.0 / 0.8)));
+    }
+
+    #[test]
+    fn pelny_wklad_wlasny_jest_bledem() {
+        assert!(gospodarstwo(Some(100.0)).oblicz().is_err());
+    }
+
+    #[test]
+    fn koszty_utrzymania_wczytywane_z_plikow() {
+        let gospodarstwo = Gospodarstwo::wczytaj("data/zdolnosc/gospodarstwo.json").unwrap();
+        let koszty = Koszty::wczytaj("data/koszty/zycie.json").unwrap().miesiecznie();
+
+        assert_eq!(gospodarstwo.utrzymanie, koszty);
+        assert_eq!(gospodarstwo.oblicz().unwrap().koszty_utrzymania, koszty);
+    }
+}